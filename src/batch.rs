@@ -0,0 +1,163 @@
+//! Non-interactive output, like `top -b`.
+
+use crate::cli::Args;
+use crate::format::format_system_stats;
+use clap::ValueEnum;
+use os_project::process::sort_processes;
+use os_project::{Collector, Player, Recorder, Snapshot};
+use std::io::{self, Write};
+use std::thread;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// System stats followed by the process table
+    Text,
+    /// One row per process, with the snapshot time in the first column
+    Csv,
+    /// One snapshot per line (JSON Lines)
+    Json,
+}
+
+/// Prints `--iterations` snapshots (or forever) to stdout, from the live
+/// system or from a recording.
+pub fn run(args: &Args) -> os_project::Result<()> {
+    let stdout = io::stdout();
+    let mut printer = Printer {
+        args,
+        out: stdout.lock(),
+        recorder: match &args.record {
+            Some(path) => Some(Recorder::create(path)?),
+            None => None,
+        },
+        printed: 0,
+    };
+
+    match &args.replay {
+        Some(path) => {
+            let mut player = Player::load(path)?;
+            while !printer.done() && printer.print(player.current().clone())? && player.step_forward()? {}
+        }
+        None => {
+            let mut collector = Collector::with_source(args.source())?;
+            collector.set_options(args.options());
+            while !printer.done() {
+                if printer.printed > 0 {
+                    thread::sleep(args.interval());
+                }
+                if !printer.print(collector.collect()?)? {
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+struct Printer<'a, W: Write> {
+    args: &'a Args,
+    out: W,
+    recorder: Option<Recorder>,
+    printed: u64,
+}
+
+impl<W: Write> Printer<'_, W> {
+    fn done(&self) -> bool {
+        self.args.iterations.is_some_and(|n| self.printed >= n)
+    }
+
+    /// Returns `false` once stdout is closed (e.g. piped into `head`).
+    fn print(&mut self, mut snapshot: Snapshot) -> os_project::Result<bool> {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record(&snapshot)?;
+        }
+
+        let selection = self.args.selection();
+        snapshot.processes.retain(|p| selection.matches(p));
+        if let Some(filter) = &self.args.filter {
+            snapshot.processes.retain(|p| filter.matches(p));
+        }
+        sort_processes(&mut snapshot.processes, self.args.sort);
+
+        let result = match self.args.format {
+            OutputFormat::Text => self.write_text(&snapshot),
+            OutputFormat::Csv => self.write_csv(&snapshot),
+            OutputFormat::Json => self.write_json(&snapshot),
+        }
+        .and_then(|()| self.out.flush());
+        self.printed += 1;
+
+        match result {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write_text(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        if self.printed > 0 {
+            writeln!(self.out)?;
+        }
+        writeln!(self.out, "{}\n", format_system_stats(snapshot))?;
+        writeln!(
+            self.out,
+            "{:>7} {:>7} {:<10} {:<2} {:>4} {:>4} {:>6} {:>10} {:>9}  COMMAND",
+            "PID", "PPID", "USER", "ST", "THR", "PR", "%CPU", "MEM", "TIME+"
+        )?;
+        for p in &snapshot.processes {
+            writeln!(
+                self.out,
+                "{:>7} {:>7} {:<10} {:<2} {:>4} {:>4} {:>6.1} {:>7.1} MB {:>9}  {}",
+                p.pid, p.ppid, p.user, p.state, p.threads, p.priority, p.cpu_usage, p.mem_usage, p.time_plus, p.command
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_csv(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        if self.printed == 0 {
+            writeln!(
+                self.out,
+                "time,pid,ppid,user,state,threads,priority,cpu_percent,mem_mb,virt_kb,res_kb,shr_kb,swap_kb,mem_percent,time_plus,command"
+            )?;
+        }
+        let time = snapshot.time.to_rfc3339();
+        for p in &snapshot.processes {
+            writeln!(
+                self.out,
+                "{},{},{},{},{},{},{},{:.1},{:.1},{},{},{},{},{:.1},{},{}",
+                time,
+                p.pid,
+                p.ppid,
+                csv_field(&p.user),
+                p.state,
+                p.threads,
+                p.priority,
+                p.cpu_usage,
+                p.mem_usage,
+                p.virt_kb,
+                p.res_kb,
+                p.shr_kb,
+                p.swap_kb,
+                p.mem_percent,
+                p.time_plus,
+                csv_field(&p.command)
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_json(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        serde_json::to_writer(&mut self.out, snapshot)?;
+        writeln!(self.out)
+    }
+}
+
+// Quotes a field if it contains a separator, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
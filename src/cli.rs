@@ -0,0 +1,148 @@
+use crate::batch::OutputFormat;
+use crate::tui::app::ViewState;
+use clap::Parser;
+use os_project::{CollectorOptions, DataSource, Filter, ProcessSelection, SortCriteria};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Terminal process and system monitor.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Args {
+    /// Seconds between refreshes
+    #[arg(short = 'd', long = "delay", default_value_t = 1.0, value_parser = parse_delay)]
+    pub delay: f64,
+
+    /// Column to sort the process list by: cpu, mem, pid, pr, virt, shr, swap, %mem, pss or uss
+    #[arg(short = 's', long, default_value = "cpu")]
+    pub sort: SortCriteria,
+
+    /// View to start in: processes, crashes, tree, network, sockets, disks or memory
+    #[arg(short = 'v', long, default_value = "processes")]
+    pub view: ViewState,
+
+    /// Only show these PIDs (comma separated or repeated)
+    #[arg(short = 'p', long = "pid", value_delimiter = ',')]
+    pub pids: Vec<i32>,
+
+    /// Only show processes of this user (name or UID)
+    #[arg(short = 'u', long)]
+    pub user: Option<String>,
+
+    /// Only show processes matching EXPR, e.g. "user==postgres && cpu>5"
+    #[arg(short = 'f', long, value_name = "EXPR")]
+    pub filter: Option<Filter>,
+
+    /// Exit after this many refreshes
+    #[arg(short = 'n', long)]
+    pub iterations: Option<u64>,
+
+    /// Read a captured tree with proc/ and sys/ under DIR instead of the live system
+    #[arg(long, value_name = "DIR")]
+    pub root: Option<PathBuf>,
+
+    /// Append every snapshot to FILE for later replay
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Show the snapshots recorded in FILE instead of the live system
+    #[arg(long, value_name = "FILE", conflicts_with = "root")]
+    pub replay: Option<PathBuf>,
+
+    /// Print to stdout instead of starting the interactive display
+    #[arg(short = 'b', long)]
+    pub batch: bool,
+
+    /// Output format of batch mode
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, requires = "batch")]
+    pub format: OutputFormat,
+
+    /// Seconds a subtree shutdown (K) waits after SIGTERM before sending SIGKILL
+    #[arg(long, value_name = "SECS", default_value_t = 5.0, value_parser = parse_grace_period)]
+    pub kill_grace: f64,
+
+    /// Serve Prometheus metrics on ADDR (e.g. 0.0.0.0:9100) instead of the display
+    #[arg(long, value_name = "ADDR", conflicts_with_all = ["batch", "replay"])]
+    pub exporter: Option<String>,
+
+    /// Export per-process series for only the N heaviest processes by CPU and by memory
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    pub exporter_top: usize,
+}
+
+impl Args {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs_f64(self.delay)
+    }
+
+    pub fn kill_grace(&self) -> Duration {
+        Duration::from_secs_f64(self.kill_grace)
+    }
+
+    pub fn source(&self) -> DataSource {
+        match &self.root {
+            Some(root) => DataSource::with_root(root),
+            None => DataSource::live(),
+        }
+    }
+
+    /// Collector options implied by the command line.
+    pub fn options(&self) -> CollectorOptions {
+        CollectorOptions {
+            read_smaps: self.sort.needs_smaps(),
+            ..CollectorOptions::default()
+        }
+    }
+
+    pub fn selection(&self) -> ProcessSelection {
+        ProcessSelection {
+            pids: self.pids.clone(),
+            user: self.user.clone(),
+        }
+    }
+}
+
+fn parse_delay(s: &str) -> Result<f64, String> {
+    let delay: f64 = s.parse().map_err(|_| format!("'{}' is not a number", s))?;
+    if !(delay.is_finite() && delay > 0.0) {
+        return Err("delay must be greater than zero".to_string());
+    }
+    // `interval` turns this into a Duration, which can't hold just any f64
+    Duration::try_from_secs_f64(delay).map_err(|_| format!("a delay of {} seconds is too long", s))?;
+    Ok(delay)
+}
+
+fn parse_grace_period(s: &str) -> Result<f64, String> {
+    let grace: f64 = s.parse().map_err(|_| format!("'{}' is not a number", s))?;
+    if !(grace.is_finite() && grace >= 0.0) {
+        return Err("grace period can't be negative".to_string());
+    }
+    Duration::try_from_secs_f64(grace).map_err(|_| format!("a grace period of {} seconds is too long", s))?;
+    Ok(grace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, clap::Error> {
+        Args::try_parse_from(["os_project"].iter().chain(args))
+    }
+
+    #[test]
+    fn delay_must_fit_a_duration() {
+        assert_eq!(parse(&["-d", "0.5"]).unwrap().interval(), Duration::from_millis(500));
+        assert!(parse(&["-d", "0"]).is_err());
+        assert!(parse(&["-d", "inf"]).is_err());
+        let error = parse(&["-b", "-n", "2", "-d", "1e30"]).unwrap_err().to_string();
+        assert!(error.contains("too long"), "{}", error);
+    }
+
+    #[test]
+    fn kill_grace_must_fit_a_duration() {
+        assert_eq!(parse(&["--kill-grace", "0"]).unwrap().kill_grace(), Duration::ZERO);
+        assert!(parse(&["--kill-grace", "-1"]).is_err());
+        let error = parse(&["--kill-grace", "1e30"]).unwrap_err().to_string();
+        assert!(error.contains("too long"), "{}", error);
+    }
+}
@@ -0,0 +1,246 @@
+use crate::crash::get_crash_logs;
+use crate::detail::ProcessDetail;
+use crate::process::{self, CpuMode, CpuTracker, Process};
+use crate::sockets::{self, SocketInfo};
+use crate::source::DataSource;
+use crate::system::{SystemSampler, SystemStats};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Everything sampled in one refresh, owned so it can outlive the collector.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub time: DateTime<Local>,
+    pub uptime: u64,
+    pub system: SystemStats,
+    pub processes: Vec<Process>,
+    #[serde(default)]
+    pub crash_events: Vec<String>,
+    /// Details of the process asked for with [`CollectorOptions::detail_pid`].
+    #[serde(default)]
+    pub detail: Option<ProcessDetail>,
+    /// Only filled in when [`CollectorOptions::read_sockets`] is set.
+    #[serde(default)]
+    pub sockets: Vec<SocketInfo>,
+}
+
+impl Snapshot {
+    pub fn process_map(&self) -> HashMap<i32, &Process> {
+        self.processes.iter().map(|p| (p.pid, p)).collect()
+    }
+}
+
+/// Settings that change what the collector reports.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CollectorOptions {
+    pub cpu_mode: CpuMode,
+    /// Leave loopback and virtual interfaces out of the network stats.
+    pub exclude_virtual_interfaces: bool,
+    /// Read PSS/USS from /proc/PID/smaps_rollup (noticeably slower).
+    pub read_smaps: bool,
+    /// Also read the full /proc details of this process.
+    pub detail_pid: Option<i32>,
+    /// Also list sockets and their owners, which means reading every
+    /// process's fd directory.
+    pub read_sockets: bool,
+    /// Read the threads of every process, not just the detail one.
+    pub read_threads: bool,
+}
+
+/// Samples `/proc` and produces [`Snapshot`]s.
+pub struct Collector {
+    source: DataSource,
+    cpu: CpuTracker,
+    system: SystemSampler,
+    read_smaps: bool,
+    detail_pid: Option<i32>,
+    read_sockets: bool,
+    read_threads: bool,
+    thread_cpu: CpuTracker,
+}
+
+impl Collector {
+    /// A collector for the running system.
+    pub fn new() -> crate::Result<Collector> {
+        Collector::with_source(DataSource::live())
+    }
+
+    pub fn with_source(source: DataSource) -> crate::Result<Collector> {
+        // Fail early rather than on the first refresh if the root is wrong
+        process::uptime(&source)?;
+        Ok(Collector {
+            source,
+            cpu: CpuTracker::new(CpuMode::default()),
+            system: SystemSampler::default(),
+            read_smaps: false,
+            detail_pid: None,
+            read_sockets: false,
+            read_threads: false,
+            thread_cpu: CpuTracker::default(),
+        })
+    }
+
+    pub fn source(&self) -> &DataSource {
+        &self.source
+    }
+
+    pub fn options(&self) -> CollectorOptions {
+        CollectorOptions {
+            cpu_mode: self.cpu.mode,
+            exclude_virtual_interfaces: self.system.exclude_virtual_interfaces,
+            read_smaps: self.read_smaps,
+            detail_pid: self.detail_pid,
+            read_sockets: self.read_sockets,
+            read_threads: self.read_threads,
+        }
+    }
+
+    pub fn set_options(&mut self, options: CollectorOptions) {
+        self.cpu.mode = options.cpu_mode;
+        self.system.exclude_virtual_interfaces = options.exclude_virtual_interfaces;
+        self.read_smaps = options.read_smaps;
+        // Thread CPU% is diffed against the last sample of the same threads,
+        // which is stale or missing once a different set is asked for
+        if (options.read_threads, options.detail_pid) != (self.read_threads, self.detail_pid) {
+            self.thread_cpu = CpuTracker::new(options.cpu_mode);
+        }
+        self.detail_pid = options.detail_pid;
+        self.read_sockets = options.read_sockets;
+        self.read_threads = options.read_threads;
+        self.thread_cpu.mode = options.cpu_mode;
+    }
+
+    pub fn collect(&mut self) -> crate::Result<Snapshot> {
+        let uptime = process::uptime(&self.source)?;
+        let system = self.system.collect(&self.source)?;
+        let crash_events = get_crash_logs(&self.source);
+
+        let mut processes = process::collect_processes(&self.source, &mut self.cpu, uptime, self.read_smaps)?;
+        process::set_mem_percent(&mut processes, system.memory.total_mb);
+        if self.read_threads || self.detail_pid.is_some() {
+            let (all, detail_pid) = (self.read_threads, self.detail_pid);
+            process::collect_threads(&self.source, &mut self.thread_cpu, &mut processes, uptime, |p| {
+                all || Some(p.pid) == detail_pid
+            });
+        }
+        let detail = self.detail_pid.and_then(|pid| ProcessDetail::collect(&self.source, pid));
+        let sockets = if self.read_sockets {
+            sockets::collect_sockets(&self.source)
+        } else {
+            Vec::new()
+        };
+
+        Ok(Snapshot {
+            time: Local::now(),
+            uptime: uptime as u64,
+            system,
+            processes,
+            crash_events,
+            detail,
+            sockets,
+        })
+    }
+
+    /// Moves the collector to a background thread that publishes a snapshot
+    /// every `interval`, so sampling never blocks the caller.
+    pub fn spawn(self, interval: Duration) -> CollectorThread {
+        let (command_tx, command_rx) = mpsc::channel();
+        let (snapshot_tx, snapshot_rx) = mpsc::channel();
+        let handle = thread::spawn(move || self.run(interval, command_rx, snapshot_tx));
+        CollectorThread {
+            commands: command_tx,
+            snapshots: snapshot_rx,
+            handle: Some(handle),
+        }
+    }
+
+    fn run(
+        mut self,
+        mut interval: Duration,
+        commands: Receiver<CollectorCommand>,
+        snapshots: Sender<crate::Result<Snapshot>>,
+    ) {
+        loop {
+            if snapshots.send(self.collect()).is_err() {
+                return;
+            }
+
+            // Sleep until the next tick, but wake up early for commands
+            match commands.recv_timeout(interval) {
+                Ok(CollectorCommand::SetOptions(options)) => self.set_options(options),
+                Ok(CollectorCommand::SetInterval(new_interval)) => interval = new_interval,
+                Ok(CollectorCommand::Refresh) | Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+}
+
+/// Requests sent to a collector running on its own thread. Each of them
+/// triggers an immediate refresh.
+#[derive(Clone, Copy, Debug)]
+pub enum CollectorCommand {
+    SetOptions(CollectorOptions),
+    SetInterval(Duration),
+    Refresh,
+}
+
+/// Handle to a collector started with [`Collector::spawn`]. Dropping it
+/// stops the thread.
+pub struct CollectorThread {
+    commands: Sender<CollectorCommand>,
+    snapshots: Receiver<crate::Result<Snapshot>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl CollectorThread {
+    pub fn send(&self, command: CollectorCommand) {
+        // Only fails once the thread is gone, which `recv` reports
+        let _ = self.commands.send(command);
+    }
+
+    /// Blocks until the next snapshot is published.
+    pub fn recv(&self) -> crate::Result<Snapshot> {
+        self.recv_collection().ok_or("collector thread stopped")?
+    }
+
+    /// Blocks until the next collection finishes and returns its outcome,
+    /// or `None` once the thread has stopped. Unlike [`Self::recv`], a
+    /// failed collection can be told apart from the thread going away.
+    pub fn recv_collection(&self) -> Option<crate::Result<Snapshot>> {
+        self.snapshots.recv().ok()
+    }
+
+    /// Whether the thread is still collecting. Once it is not, `recv` and
+    /// `try_recv` fail every time.
+    pub fn is_running(&self) -> bool {
+        self.handle.as_ref().is_some_and(|handle| !handle.is_finished())
+    }
+
+    /// Returns the most recent pending snapshot, if any, dropping older ones.
+    pub fn try_recv(&self) -> crate::Result<Option<Snapshot>> {
+        let mut latest = None;
+        loop {
+            match self.snapshots.try_recv() {
+                Ok(snapshot) => latest = Some(snapshot?),
+                Err(TryRecvError::Empty) => return Ok(latest),
+                Err(TryRecvError::Disconnected) => return Err("collector thread stopped".into()),
+            }
+        }
+    }
+}
+
+impl Drop for CollectorThread {
+    fn drop(&mut self) {
+        // Closing the command channel makes the thread exit at its next wait
+        let (closed, _) = mpsc::channel();
+        self.commands = closed;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::source::DataSource;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead};
+
+/// Cumulative jiffies of one `cpu` line of /proc/stat.
+#[derive(Clone, Copy, Debug, Default)]
+struct CpuTimes {
+    user: u64,
+    nice: u64,
+    system: u64,
+    idle: u64,
+    iowait: u64,
+    irq: u64,
+    softirq: u64,
+    steal: u64,
+}
+
+impl CpuTimes {
+    fn parse(fields: &[&str]) -> CpuTimes {
+        let field = |i: usize| fields.get(i).and_then(|v| v.parse().ok()).unwrap_or(0);
+        CpuTimes {
+            user: field(0),
+            nice: field(1),
+            system: field(2),
+            idle: field(3),
+            iowait: field(4),
+            irq: field(5),
+            softirq: field(6),
+            steal: field(7),
+        }
+    }
+
+    fn total(&self) -> u64 {
+        self.user + self.nice + self.system + self.idle + self.iowait + self.irq + self.softirq + self.steal
+    }
+}
+
+/// Share of time, in percent, spent in each state over the last interval.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CpuUsage {
+    pub user: f64,
+    pub system: f64,
+    pub nice: f64,
+    pub idle: f64,
+    pub iowait: f64,
+    pub irq: f64,
+    pub softirq: f64,
+    pub steal: f64,
+}
+
+impl CpuUsage {
+    fn between(previous: &CpuTimes, current: &CpuTimes) -> CpuUsage {
+        let total = current.total().saturating_sub(previous.total());
+        if total == 0 {
+            return CpuUsage {
+                idle: 100.0,
+                ..CpuUsage::default()
+            };
+        }
+        let percent = |now: u64, before: u64| now.saturating_sub(before) as f64 * 100.0 / total as f64;
+        CpuUsage {
+            user: percent(current.user, previous.user),
+            system: percent(current.system, previous.system),
+            nice: percent(current.nice, previous.nice),
+            idle: percent(current.idle, previous.idle),
+            iowait: percent(current.iowait, previous.iowait),
+            irq: percent(current.irq, previous.irq),
+            softirq: percent(current.softirq, previous.softirq),
+            steal: percent(current.steal, previous.steal),
+        }
+    }
+
+    /// Everything that is neither idle nor waiting on I/O.
+    pub fn busy(&self) -> f64 {
+        (100.0 - self.idle - self.iowait).max(0.0)
+    }
+}
+
+/// Aggregate and per-core utilisation.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CpuStats {
+    pub total: CpuUsage,
+    pub cores: Vec<CpuUsage>,
+}
+
+/// Diffs successive /proc/stat samples. The first sample is measured
+/// against boot, i.e. it reports the average since the machine came up.
+#[derive(Default)]
+pub struct CpuSampler {
+    previous: Option<(CpuTimes, Vec<CpuTimes>)>,
+}
+
+impl CpuSampler {
+    pub fn sample(&mut self, source: &DataSource) -> io::Result<CpuStats> {
+        let (total, cores) = read_cpu_times(source)?;
+        let (previous_total, previous_cores) = self.previous.take().unwrap_or_default();
+
+        let stats = CpuStats {
+            total: CpuUsage::between(&previous_total, &total),
+            cores: cores
+                .iter()
+                .enumerate()
+                .map(|(i, core)| {
+                    let previous = previous_cores.get(i).copied().unwrap_or_default();
+                    CpuUsage::between(&previous, core)
+                })
+                .collect(),
+        };
+
+        self.previous = Some((total, cores));
+        Ok(stats)
+    }
+}
+
+fn read_cpu_times(source: &DataSource) -> io::Result<(CpuTimes, Vec<CpuTimes>)> {
+    let file = source.open_proc("stat")?;
+    let reader = io::BufReader::new(file);
+
+    let mut total = CpuTimes::default();
+    let mut cores = vec![];
+
+    for line in reader.lines().map_while(Result::ok) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.first() {
+            Some(&"cpu") => total = CpuTimes::parse(&fields[1..]),
+            Some(name) if name.starts_with("cpu") => cores.push(CpuTimes::parse(&fields[1..])),
+            _ => {}
+        }
+    }
+
+    Ok((total, cores))
+}
@@ -0,0 +1,29 @@
+use crate::source::DataSource;
+use std::fs;
+use std::process::Command;
+
+/// Returns the kernel log lines that look like segfaults or OOM kills.
+///
+/// The live system is asked through `dmesg`; a captured source is read from
+/// its `dmesg` dump. An empty list is returned if neither is available
+/// (e.g. missing permissions), so callers never have to special-case it.
+pub fn get_crash_logs(source: &DataSource) -> Vec<String> {
+    let logs = match source.dmesg_path() {
+        Some(path) => fs::read_to_string(path).unwrap_or_default(),
+        None => {
+            // Run `dmesg` and capture the output
+            match Command::new("dmesg")
+                .arg("--ctime") // Include human-readable timestamps
+                .output()
+            {
+                Ok(output) => String::from_utf8_lossy(&output.stdout).into_owned(),
+                Err(_) => return Vec::new(),
+            }
+        }
+    };
+
+    logs.lines()
+        .filter(|line| line.contains("segfault") || line.contains("oom"))
+        .map(|line| line.to_string())
+        .collect()
+}
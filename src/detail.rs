@@ -0,0 +1,139 @@
+use crate::files::{self, OpenFile};
+use crate::source::DataSource;
+use chrono::{DateTime, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader};
+
+/// One row of /proc/PID/limits.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Limit {
+    pub name: String,
+    pub soft: String,
+    pub hard: String,
+    pub units: String,
+}
+
+/// Everything /proc knows about a single process, read on demand for the
+/// detail pane rather than for every process on every refresh.
+///
+/// Links and files that can't be read (typically another user's process
+/// without root) are left empty or `None`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessDetail {
+    pub pid: i32,
+    /// The `Key: value` lines of /proc/PID/status, in file order.
+    pub status: Vec<(String, String)>,
+    pub limits: Vec<Limit>,
+    pub cwd: Option<String>,
+    pub root: Option<String>,
+    pub exe: Option<String>,
+    pub cmdline: Vec<String>,
+    /// `None` when the environment is not readable.
+    pub environ: Option<Vec<String>>,
+    pub start_time: Option<DateTime<Local>>,
+    /// Lines of /proc/PID/cgroup, e.g. `0::/user.slice`.
+    pub cgroups: Vec<String>,
+    /// Namespace type and link target, e.g. `("net", "net:[4026531840]")`.
+    pub namespaces: Vec<(String, String)>,
+    pub open_files: Vec<OpenFile>,
+}
+
+impl ProcessDetail {
+    /// Returns `None` once the process is gone.
+    pub fn collect(source: &DataSource, pid: i32) -> Option<ProcessDetail> {
+        let dir = source.proc_path(pid.to_string());
+        let status = fs::File::open(dir.join("status")).ok()?;
+        let link = |name: &str| fs::read_link(dir.join(name)).ok().map(|p| p.display().to_string());
+
+        let mut namespaces: Vec<(String, String)> = fs::read_dir(dir.join("ns"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let target = fs::read_link(entry.path()).ok()?;
+                Some((entry.file_name().to_string_lossy().to_string(), target.display().to_string()))
+            })
+            .collect();
+        namespaces.sort();
+
+        Some(ProcessDetail {
+            pid,
+            status: BufReader::new(status)
+                .lines()
+                .map_while(Result::ok)
+                .filter_map(|line| {
+                    let (key, value) = line.split_once(':')?;
+                    // Uid, Gid and a few others are tab separated lists
+                    let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+                    Some((key.to_string(), value))
+                })
+                .collect(),
+            limits: fs::read_to_string(dir.join("limits"))
+                .map(|limits| parse_limits(&limits))
+                .unwrap_or_default(),
+            cwd: link("cwd"),
+            root: link("root"),
+            exe: link("exe"),
+            cmdline: fs::read(dir.join("cmdline"))
+                .map(|data| split_nul(&data))
+                .unwrap_or_default(),
+            environ: fs::read(dir.join("environ")).ok().map(|data| split_nul(&data)),
+            start_time: start_time(source, pid),
+            cgroups: fs::read_to_string(dir.join("cgroup"))
+                .map(|cgroup| cgroup.lines().map(str::to_string).collect())
+                .unwrap_or_default(),
+            namespaces,
+            open_files: files::open_files(source, pid),
+        })
+    }
+}
+
+fn split_nul(data: &[u8]) -> Vec<String> {
+    data.split(|&b| b == 0)
+        .filter(|part| !part.is_empty())
+        .map(|part| String::from_utf8_lossy(part).to_string())
+        .collect()
+}
+
+/// The limits file is a fixed-width table; the header gives the columns.
+fn parse_limits(limits: &str) -> Vec<Limit> {
+    let mut lines = limits.lines();
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let (Some(soft), Some(hard), Some(units)) =
+        (header.find("Soft Limit"), header.find("Hard Limit"), header.find("Units"))
+    else {
+        return Vec::new();
+    };
+    let column = |line: &str, from: usize, to: usize| {
+        line.get(from..to.min(line.len())).unwrap_or("").trim().to_string()
+    };
+    lines
+        .map(|line| Limit {
+            name: column(line, 0, soft),
+            soft: column(line, soft, hard),
+            hard: column(line, hard, units),
+            units: column(line, units, line.len()),
+        })
+        .collect()
+}
+
+/// Boot time plus the `starttime` clock ticks from /proc/PID/stat.
+fn start_time(source: &DataSource, pid: i32) -> Option<DateTime<Local>> {
+    let stat = fs::read_to_string(source.proc_path(format!("{}/stat", pid))).ok()?;
+    // The command name can hold spaces and parentheses, so count fields
+    // from the last ')'; starttime is field 22, the 20th after it.
+    let (_, rest) = stat.rsplit_once(')')?;
+    let starttime: u64 = rest.split_whitespace().nth(19)?.parse().ok()?;
+
+    let boot_time: i64 = BufReader::new(source.open_proc("stat").ok()?)
+        .lines()
+        .map_while(Result::ok)
+        .find_map(|line| line.strip_prefix("btime ").and_then(|v| v.trim().parse().ok()))?;
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as u64;
+
+    Local.timestamp_opt(boot_time + (starttime / ticks) as i64, 0).single()
+}
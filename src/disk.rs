@@ -0,0 +1,148 @@
+use crate::source::DataSource;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead};
+use std::time::Instant;
+
+/// Cumulative counters of one line of /proc/diskstats.
+#[derive(Clone, Copy, Debug, Default)]
+struct Counters {
+    reads: u64,
+    sectors_read: u64,
+    read_ms: u64,
+    writes: u64,
+    sectors_written: u64,
+    write_ms: u64,
+    io_ms: u64,
+}
+
+/// Per-device I/O over the last refresh interval.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DiskStats {
+    pub name: String,
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+    pub read_iops: f64,
+    pub write_iops: f64,
+    /// Average time, in milliseconds, a request took to be served.
+    pub await_ms: f64,
+    /// Share of the interval the device had I/O in flight.
+    pub util_percent: f64,
+    pub read_bytes_total: u64,
+    pub write_bytes_total: u64,
+    /// Partitions of a whole disk; always empty for a partition.
+    pub partitions: Vec<DiskStats>,
+}
+
+/// Diffs successive /proc/diskstats samples. Loop and ram devices are left
+/// out, and partitions are reported under their parent disk so nothing is
+/// counted twice.
+#[derive(Default)]
+pub struct DiskSampler {
+    previous: HashMap<String, Counters>,
+    last_sample: Option<Instant>,
+}
+
+impl DiskSampler {
+    pub fn sample(&mut self, source: &DataSource) -> io::Result<Vec<DiskStats>> {
+        let counters = read_diskstats(source)?;
+        let now = Instant::now();
+        let elapsed = self
+            .last_sample
+            .map(|last| now.duration_since(last).as_secs_f64())
+            .unwrap_or(0.0);
+
+        let mut disks: Vec<DiskStats> = Vec::new();
+        let mut partitions: Vec<(String, DiskStats)> = Vec::new();
+        for (name, current) in &counters {
+            if name.starts_with("loop") || name.starts_with("ram") {
+                continue;
+            }
+            let previous = self.previous.get(name).copied().unwrap_or(*current);
+            let stats = DiskStats::between(name, &previous, current, elapsed);
+            match parent_disk(source, name) {
+                Some(parent) => partitions.push((parent, stats)),
+                None => disks.push(stats),
+            }
+        }
+        for (parent, partition) in partitions {
+            if let Some(disk) = disks.iter_mut().find(|d| d.name == parent) {
+                disk.partitions.push(partition);
+            }
+        }
+
+        self.previous = counters.into_iter().collect();
+        self.last_sample = Some(now);
+        Ok(disks)
+    }
+}
+
+impl DiskStats {
+    fn between(name: &str, previous: &Counters, current: &Counters, elapsed: f64) -> DiskStats {
+        let delta = |now: u64, before: u64| now.saturating_sub(before) as f64;
+        let rate = |now: u64, before: u64| {
+            if elapsed > 0.0 {
+                delta(now, before) / elapsed
+            } else {
+                0.0
+            }
+        };
+
+        let ios = delta(current.reads, previous.reads) + delta(current.writes, previous.writes);
+        let io_time = delta(current.read_ms, previous.read_ms) + delta(current.write_ms, previous.write_ms);
+
+        DiskStats {
+            name: name.to_string(),
+            read_bytes_per_sec: rate(current.sectors_read, previous.sectors_read) * 512.0,
+            write_bytes_per_sec: rate(current.sectors_written, previous.sectors_written) * 512.0,
+            read_iops: rate(current.reads, previous.reads),
+            write_iops: rate(current.writes, previous.writes),
+            await_ms: if ios > 0.0 { io_time / ios } else { 0.0 },
+            util_percent: (rate(current.io_ms, previous.io_ms) / 10.0).min(100.0),
+            read_bytes_total: current.sectors_read * 512, // Sectors to bytes
+            write_bytes_total: current.sectors_written * 512,
+            partitions: Vec::new(),
+        }
+    }
+}
+
+fn read_diskstats(source: &DataSource) -> io::Result<Vec<(String, Counters)>> {
+    let file = source.open_proc("diskstats")?;
+    let reader = io::BufReader::new(file);
+
+    let mut devices = Vec::new();
+
+    for line in reader.lines().map_while(Result::ok) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() > 13 {
+            let field = |i: usize| fields[i].parse::<u64>().unwrap_or(0);
+            devices.push((
+                fields[2].to_string(),
+                Counters {
+                    reads: field(3),
+                    sectors_read: field(5),
+                    read_ms: field(6),
+                    writes: field(7),
+                    sectors_written: field(9),
+                    write_ms: field(10),
+                    io_ms: field(12),
+                },
+            ));
+        }
+    }
+
+    Ok(devices)
+}
+
+// A partition shows up in sysfs as a directory inside its disk's one,
+// e.g. /sys/class/block/sda1 -> ../../devices/.../block/sda/sda1
+fn parent_disk(source: &DataSource, name: &str) -> Option<String> {
+    let link = source.sys_path("class/block").join(name.replace('/', "!"));
+    if !link.join("partition").exists() {
+        return None;
+    }
+    let target = fs::read_link(&link).ok()?;
+    let parent = target.parent()?.file_name()?.to_string_lossy().replace('!', "/");
+    Some(parent)
+}
@@ -0,0 +1,79 @@
+//! `--exporter` mode: serves the latest snapshot as Prometheus metrics.
+
+use crate::cli::Args;
+use os_project::{metrics, Collector, Snapshot};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub fn run(args: &Args, addr: &str) -> os_project::Result<()> {
+    let collector = Collector::with_source(args.source())?.spawn(args.interval());
+    let latest = Arc::new(Mutex::new(collector.recv()?));
+
+    let listener = TcpListener::bind(addr)?;
+    eprintln!("Serving metrics on http://{}/metrics", listener.local_addr()?);
+
+    let top = args.exporter_top;
+    let served = Arc::clone(&latest);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let snapshot = served.lock().unwrap().clone();
+            // A client hanging up mid-request is not our problem
+            let _ = serve(stream, &snapshot, top);
+        }
+    });
+
+    // Keep only the newest snapshot around for the next scrape. One failed
+    // collection must not take the endpoint down: scrapers get the last good
+    // snapshot until the next one succeeds
+    loop {
+        match collector.recv_collection().ok_or("collector thread stopped")? {
+            Ok(snapshot) => *latest.lock().unwrap() = snapshot,
+            Err(e) => eprintln!("Collection failed, serving the previous snapshot: {}", e),
+        }
+    }
+}
+
+fn serve(stream: TcpStream, snapshot: &Snapshot, top: usize) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain the headers so closing the socket does not reset the connection
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/metrics") => (
+            "200 OK",
+            "text/plain; version=0.0.4",
+            metrics::render(snapshot, top),
+        ),
+        ("GET", "/") => (
+            "200 OK",
+            "text/html",
+            "<html><body><a href=\"/metrics\">Metrics</a></body></html>\n".to_string(),
+        ),
+        _ => ("404 Not Found", "text/plain", "Not found\n".to_string()),
+    };
+
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
@@ -0,0 +1,164 @@
+use crate::source::DataSource;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// What a file descriptor points at, from the shape of its /proc/PID/fd link.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FdKind {
+    File,
+    Socket,
+    Pipe,
+    EventFd,
+    /// Any other `anon_inode:` (epoll, timerfd, signalfd, inotify, ...).
+    AnonInode,
+    #[default]
+    Other,
+}
+
+impl FdKind {
+    fn from_target(target: &str) -> FdKind {
+        if target.starts_with('/') {
+            FdKind::File
+        } else if target.starts_with("socket:") {
+            FdKind::Socket
+        } else if target.starts_with("pipe:") {
+            FdKind::Pipe
+        } else if target == "anon_inode:[eventfd]" {
+            FdKind::EventFd
+        } else if target.starts_with("anon_inode:") {
+            FdKind::AnonInode
+        } else {
+            FdKind::Other
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FdKind::File => "file",
+            FdKind::Socket => "socket",
+            FdKind::Pipe => "pipe",
+            FdKind::EventFd => "eventfd",
+            FdKind::AnonInode => "anon_inode",
+            FdKind::Other => "other",
+        }
+    }
+}
+
+/// One entry of /proc/PID/fd, with the offset and flags from fdinfo.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct OpenFile {
+    pub fd: i32,
+    pub target: String,
+    pub kind: FdKind,
+    pub pos: Option<u64>,
+    /// `open(2)` flags, as the octal number in fdinfo.
+    pub flags: Option<u32>,
+}
+
+impl OpenFile {
+    /// Access mode and the common status flags, e.g. `rw,append,cloexec`.
+    pub fn flags_description(&self) -> String {
+        let Some(flags) = self.flags.map(|f| f as i32) else {
+            return String::new();
+        };
+        let mut parts = vec![match flags & libc::O_ACCMODE {
+            libc::O_RDONLY => "r",
+            libc::O_WRONLY => "w",
+            _ => "rw",
+        }];
+        for (flag, name) in [
+            (libc::O_APPEND, "append"),
+            (libc::O_NONBLOCK, "nonblock"),
+            (libc::O_SYNC, "sync"),
+            (libc::O_DIRECT, "direct"),
+            (libc::O_CLOEXEC, "cloexec"),
+        ] {
+            if flags & flag == flag {
+                parts.push(name);
+            }
+        }
+        parts.join(",")
+    }
+}
+
+/// The open file descriptors of a process, by fd number. Empty if the
+/// process is gone or not ours to inspect.
+pub fn open_files(source: &DataSource, pid: i32) -> Vec<OpenFile> {
+    let dir = source.proc_path(pid.to_string());
+    let mut files: Vec<OpenFile> = fs::read_dir(dir.join("fd"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let fd: i32 = entry.file_name().to_str()?.parse().ok()?;
+            let target = fs::read_link(entry.path()).ok()?.display().to_string();
+            let (pos, flags) = read_fdinfo(&dir.join("fdinfo").join(fd.to_string()));
+            Some(OpenFile {
+                fd,
+                kind: FdKind::from_target(&target),
+                target,
+                pos,
+                flags,
+            })
+        })
+        .collect();
+    files.sort_by_key(|f| f.fd);
+    files
+}
+
+fn read_fdinfo(path: &Path) -> (Option<u64>, Option<u32>) {
+    let Ok(fdinfo) = fs::read_to_string(path) else {
+        return (None, None);
+    };
+    let field = |name: &str| {
+        fdinfo
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .map(|value| value.trim().to_string())
+    };
+    (
+        field("pos:").and_then(|pos| pos.parse().ok()),
+        field("flags:").and_then(|flags| u32::from_str_radix(&flags, 8).ok()),
+    )
+}
+
+/// A process holding a file open, found by [`find_open_file`].
+#[derive(Clone, Debug)]
+pub struct FileUser {
+    pub pid: i32,
+    pub command: String,
+    pub file: OpenFile,
+}
+
+/// Every descriptor, in any process we can inspect, whose target contains
+/// `query` (a path, or e.g. `socket:[12345]`), like `lsof FILE`.
+pub fn find_open_file(source: &DataSource, query: &str) -> Vec<FileUser> {
+    let mut users = Vec::new();
+    let mut pids: Vec<i32> = fs::read_dir(source.proc_root())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+        .collect();
+    pids.sort_unstable();
+
+    for pid in pids {
+        let matching: Vec<OpenFile> = open_files(source, pid)
+            .into_iter()
+            .filter(|file| file.target.contains(query))
+            .collect();
+        if matching.is_empty() {
+            continue;
+        }
+        let command = fs::read_to_string(source.proc_path(format!("{}/comm", pid)))
+            .map(|comm| comm.trim_end().to_string())
+            .unwrap_or_default();
+        users.extend(matching.into_iter().map(|file| FileUser {
+            pid,
+            command: command.clone(),
+            file,
+        }));
+    }
+    users
+}
@@ -0,0 +1,512 @@
+use crate::process::Process;
+use std::str::FromStr;
+
+/// A parsed process filter such as `user==postgres && cpu>5 && state==D`.
+///
+/// Comparisons are `field op value`, joined with `&&` and `||`, negated with
+/// `!` and grouped with parentheses. The fields are pid, ppid, user, state,
+/// threads, priority (pr), cpu, mem and command (cmd). The operators are
+/// `==`, `!=`, `<`, `<=`, `>`, `>=` and `~` (contains, text fields only).
+/// `mem` takes a size such as `500MB` or `1.5G`; a bare number means MiB.
+/// A word on its own matches the command name, the command line or the PID.
+/// Terms side by side must all match, so `user==www nginx` means
+/// `user==www && nginx` and `nginx worker` finds command lines containing
+/// both words; quote a phrase to match it as a whole (`"worker process"`).
+#[derive(Clone, Debug)]
+pub struct Filter {
+    text: String,
+    expr: Expr,
+}
+
+impl Filter {
+    pub fn matches(&self, p: &Process) -> bool {
+        self.expr.eval(p)
+    }
+
+    /// The filter as it was typed.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    /// Errors carry the 1-based column they were found at.
+    fn from_str(s: &str) -> Result<Filter, String> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            pos: 0,
+            end: s.chars().count() + 1,
+        };
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(Filter {
+                text: s.to_string(),
+                expr,
+            }),
+            Some(token) => Err(format!("unexpected '{}' at column {}", token.kind, token.column)),
+        }
+    }
+}
+
+/// Case-insensitive match on the command name or line, or an exact PID.
+pub fn matches_text(p: &Process, text: &str) -> bool {
+    let text = text.to_lowercase();
+    p.pid.to_string() == text
+        || p.command.to_lowercase().contains(&text)
+        || p.cmdline.to_lowercase().contains(&text)
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Number(Field, Op, f64),
+    Text(Field, Op, String),
+    Word(String),
+}
+
+impl Expr {
+    fn eval(&self, p: &Process) -> bool {
+        match self {
+            Expr::And(a, b) => a.eval(p) && b.eval(p),
+            Expr::Or(a, b) => a.eval(p) || b.eval(p),
+            Expr::Not(e) => !e.eval(p),
+            Expr::Number(field, op, value) => op.compare(field.number(p), *value),
+            Expr::Text(field, op, value) => {
+                let matched = match (field, op) {
+                    (Field::User, Op::Contains) => p.user.contains(value.as_str()),
+                    (Field::User, _) => p.user == *value || p.uid.to_string() == *value,
+                    (Field::State, Op::Contains) => value.contains(p.state),
+                    (Field::State, _) => p.state.to_string() == *value,
+                    (_, Op::Contains) => {
+                        let value = value.to_lowercase();
+                        p.command.to_lowercase().contains(&value) || p.cmdline.to_lowercase().contains(&value)
+                    }
+                    _ => p.command == *value,
+                };
+                if *op == Op::Ne {
+                    !matched
+                } else {
+                    matched
+                }
+            }
+            Expr::Word(word) => matches_text(p, word),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Pid,
+    Ppid,
+    User,
+    State,
+    Threads,
+    Priority,
+    Cpu,
+    Mem,
+    Command,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Field> {
+        match name.to_ascii_lowercase().as_str() {
+            "pid" => Some(Field::Pid),
+            "ppid" => Some(Field::Ppid),
+            "user" => Some(Field::User),
+            "state" => Some(Field::State),
+            "threads" => Some(Field::Threads),
+            "priority" | "pr" => Some(Field::Priority),
+            "cpu" => Some(Field::Cpu),
+            "mem" => Some(Field::Mem),
+            "command" | "cmd" => Some(Field::Command),
+            _ => None,
+        }
+    }
+
+    fn is_text(self) -> bool {
+        matches!(self, Field::User | Field::State | Field::Command)
+    }
+
+    fn number(self, p: &Process) -> f64 {
+        match self {
+            Field::Pid => p.pid as f64,
+            Field::Ppid => p.ppid as f64,
+            Field::Threads => p.threads as f64,
+            Field::Priority => p.priority as f64,
+            Field::Cpu => p.cpu_usage,
+            Field::Mem => p.mem_usage * 1024.0 * 1024.0,
+            Field::User | Field::State | Field::Command => 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+impl Op {
+    fn compare(self, a: f64, b: f64) -> bool {
+        match self {
+            Op::Eq => a == b,
+            Op::Ne => a != b,
+            Op::Lt => a < b,
+            Op::Le => a <= b,
+            Op::Gt => a > b,
+            Op::Ge => a >= b,
+            Op::Contains => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match self {
+            TokenKind::Word(word) => word,
+            TokenKind::Op(Op::Eq) => "==",
+            TokenKind::Op(Op::Ne) => "!=",
+            TokenKind::Op(Op::Lt) => "<",
+            TokenKind::Op(Op::Le) => "<=",
+            TokenKind::Op(Op::Gt) => ">",
+            TokenKind::Op(Op::Ge) => ">=",
+            TokenKind::Op(Op::Contains) => "~",
+            TokenKind::And => "&&",
+            TokenKind::Or => "||",
+            TokenKind::Not => "!",
+            TokenKind::Open => "(",
+            TokenKind::Close => ")",
+        };
+        f.write_str(s)
+    }
+}
+
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let column = i + 1;
+        let next = chars.get(i + 1).copied();
+        let (kind, len) = match (chars[i], next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('=', Some('=')) => (TokenKind::Op(Op::Eq), 2),
+            ('=', _) => (TokenKind::Op(Op::Eq), 1),
+            ('!', Some('=')) => (TokenKind::Op(Op::Ne), 2),
+            ('!', _) => (TokenKind::Not, 1),
+            ('<', Some('=')) => (TokenKind::Op(Op::Le), 2),
+            ('<', _) => (TokenKind::Op(Op::Lt), 1),
+            ('>', Some('=')) => (TokenKind::Op(Op::Ge), 2),
+            ('>', _) => (TokenKind::Op(Op::Gt), 1),
+            ('~', _) => (TokenKind::Op(Op::Contains), 1),
+            ('&', Some('&')) => (TokenKind::And, 2),
+            ('|', Some('|')) => (TokenKind::Or, 2),
+            ('(', _) => (TokenKind::Open, 1),
+            (')', _) => (TokenKind::Close, 1),
+            ('&', _) | ('|', _) => {
+                return Err(format!("expected '{0}{0}' at column {1}", chars[i], column));
+            }
+            (quote @ ('"' | '\''), _) => {
+                let Some(len) = chars[i + 1..].iter().position(|&c| c == quote) else {
+                    return Err(format!("unterminated string at column {}", column));
+                };
+                let word = chars[i + 1..i + 1 + len].iter().collect();
+                (TokenKind::Word(word), len + 2)
+            }
+            _ => {
+                let len = chars[i..]
+                    .iter()
+                    .position(|&c| c.is_whitespace() || "=!<>~&|()\"'".contains(c))
+                    .unwrap_or(chars.len() - i);
+                (TokenKind::Word(chars[i..i + len].iter().collect()), len)
+            }
+        };
+        tokens.push(Token { kind, column });
+        i += len;
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    // Column reported for errors at the end of the input
+    end: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        if self.peek().is_some_and(|t| t.kind == *kind) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.eat(&TokenKind::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        // A term right after another one is joined to it with an implicit `&&`
+        while self.eat(&TokenKind::And)
+            || self
+                .peek()
+                .is_some_and(|t| matches!(t.kind, TokenKind::Word(_) | TokenKind::Not | TokenKind::Open))
+        {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        let end = self.end;
+        let Some(token) = self.next() else {
+            return Err(format!("expected a condition at column {}", end));
+        };
+        let column = token.column;
+        match &token.kind {
+            TokenKind::Not => Ok(Expr::Not(Box::new(self.unary()?))),
+            TokenKind::Open => {
+                let expr = self.or()?;
+                if self.eat(&TokenKind::Close) {
+                    Ok(expr)
+                } else {
+                    let column = self.peek().map_or(end, |t| t.column);
+                    Err(format!("expected ')' at column {}", column))
+                }
+            }
+            TokenKind::Word(word) => {
+                let word = word.clone();
+                match self.peek().map(|t| &t.kind) {
+                    Some(TokenKind::Op(_)) => self.comparison(&word, column),
+                    _ => Ok(Expr::Word(word)),
+                }
+            }
+            kind => Err(format!("unexpected '{}' at column {}", kind, column)),
+        }
+    }
+
+    fn comparison(&mut self, name: &str, column: usize) -> Result<Expr, String> {
+        let Some(field) = Field::from_name(name) else {
+            return Err(format!(
+                "unknown field '{}' at column {} (expected pid, ppid, user, state, threads, priority, cpu, mem or command)",
+                name, column
+            ));
+        };
+        let end = self.end;
+        let Some(Token { kind: TokenKind::Op(op), column: op_column }) = self.next() else {
+            unreachable!("comparison() is only called before an operator");
+        };
+        let (op, op_column) = (*op, *op_column);
+        let (value, value_column) = match self.next() {
+            Some(Token { kind: TokenKind::Word(value), column }) => (value.clone(), *column),
+            Some(token) => return Err(format!("expected a value at column {}", token.column)),
+            None => return Err(format!("expected a value at column {}", end)),
+        };
+
+        if field.is_text() {
+            if !matches!(op, Op::Eq | Op::Ne | Op::Contains) {
+                return Err(format!("'{}' can only be compared with ==, != or ~ (column {})", name, op_column));
+            }
+            return Ok(Expr::Text(field, op, value));
+        }
+        if op == Op::Contains {
+            return Err(format!("'~' needs a text field (column {})", op_column));
+        }
+        let number = if field == Field::Mem {
+            parse_size(&value)
+        } else {
+            value.parse().ok()
+        };
+        match number {
+            Some(number) => Ok(Expr::Number(field, op, number)),
+            None if field == Field::Mem => {
+                Err(format!("'{}' is not a size like 500MB (column {})", value, value_column))
+            }
+            None => Err(format!("'{}' is not a number (column {})", value, value_column)),
+        }
+    }
+}
+
+/// Parses `500MB`, `1.5G`, `64k` and so on into bytes. Units are powers of
+/// 1024 and a bare number is in MiB, like the MEM column.
+fn parse_size(s: &str) -> Option<f64> {
+    let split = s.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let scale = match unit.to_ascii_lowercase().as_str() {
+        "b" => 1.0,
+        "k" | "kb" | "kib" => 1024.0,
+        "" | "m" | "mb" | "mib" => 1024.0 * 1024.0,
+        "g" | "gb" | "gib" => 1024.0 * 1024.0 * 1024.0,
+        "t" | "tb" | "tib" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(number * scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn nginx() -> Process {
+        serde_json::from_value(json!({
+            "pid": 42,
+            "ppid": 1,
+            "uid": 33,
+            "user": "www",
+            "state": "S",
+            "threads": 4,
+            "priority": 20,
+            "cpu_usage": 12.5,
+            "mem_usage": 512.0,
+            "time_plus": "00:01:00",
+            "command": "nginx",
+            "cmdline": "nginx: worker process",
+        }))
+        .unwrap()
+    }
+
+    fn matches(filter: &str) -> bool {
+        filter.parse::<Filter>().unwrap().matches(&nginx())
+    }
+
+    fn error(filter: &str) -> String {
+        filter.parse::<Filter>().unwrap_err()
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert!(matches("pid==1 || user==www && cpu>10"));
+        assert!(!matches("pid==1 || user==root && cpu>10"));
+        assert!(!matches("(pid==1 || user==www) && cpu>50"));
+        assert!(matches("!pid==1 && !(cpu>50 || state==D)"));
+    }
+
+    #[test]
+    fn adjacent_terms_are_and_ed() {
+        assert!(matches("user==www nginx"));
+        assert!(!matches("user==www apache"));
+        assert!(matches("cpu>10 (pid==1 || ppid==1)"));
+    }
+
+    #[test]
+    fn words_are_and_ed_with_or_without_other_terms() {
+        assert!(matches("nginx: worker"));
+        assert!(matches("process worker"));
+        assert!(!matches("process apache"));
+        assert!(matches("process worker user==www"));
+        assert!(!matches("process worker user==root"));
+    }
+
+    #[test]
+    fn quoted_phrases_match_as_a_whole() {
+        assert!(matches("\"worker process\""));
+        assert!(!matches("\"process worker\""));
+        assert!(matches("'worker process' && user==www"));
+        assert!(!matches("'process worker' && user==www"));
+    }
+
+    #[test]
+    fn text_fields() {
+        assert!(matches("user==33"));
+        assert!(matches("user~ww"));
+        assert!(matches("state~SD"));
+        assert!(!matches("state!=S"));
+        assert!(matches("cmd~NGINX"));
+        assert!(matches("cmd~worker"));
+        // The PID is only matched by a bare word, not by a command field
+        assert!(!matches("cmd~42"));
+        assert!(!matches("cmd~4"));
+        assert!(!matches("cmd!=nginx"));
+        assert!(matches("42"));
+    }
+
+    #[test]
+    fn mem_takes_size_units() {
+        assert!(matches("mem==512"));
+        assert!(matches("mem>500MB"));
+        assert!(matches("mem>=524288k"));
+        assert!(matches("mem<0.6G"));
+        assert!(!matches("mem>0.5GiB"));
+        assert!(!matches("mem>1T"));
+        assert_eq!(parse_size("64b"), Some(64.0));
+        assert_eq!(parse_size("1.5"), Some(1.5 * 1024.0 * 1024.0));
+        assert_eq!(parse_size("5XB"), None);
+    }
+
+    #[test]
+    fn errors_report_columns() {
+        assert_eq!(error("mem>5XB"), "'5XB' is not a size like 500MB (column 5)");
+        assert_eq!(error("cpu>lots"), "'lots' is not a number (column 5)");
+        assert_eq!(error("cpu>"), "expected a value at column 5");
+        assert_eq!(error("cpu> &&"), "expected a value at column 6");
+        assert_eq!(error("user<root"), "'user' can only be compared with ==, != or ~ (column 5)");
+        assert_eq!(error("cpu~1"), "'~' needs a text field (column 4)");
+        assert_eq!(error("(cpu>1"), "expected ')' at column 7");
+        assert_eq!(error("cpu>1 &"), "expected '&&' at column 7");
+        assert_eq!(error("'nginx"), "unterminated string at column 1");
+        assert_eq!(error("nginx)"), "unexpected ')' at column 6");
+        assert_eq!(error("user==www &&"), "expected a condition at column 13");
+        assert!(error("bogus==1").starts_with("unknown field 'bogus' at column 1"));
+    }
+
+    #[test]
+    fn every_operator_reaches_comparison() {
+        // `comparison` assumes the token after the field is an operator
+        for op in ["==", "=", "!=", "<", "<=", ">", ">=", "~"] {
+            assert!(format!("pid{}", op).parse::<Filter>().is_err(), "pid{}", op);
+            let numeric = format!("pid {} 42", op).parse::<Filter>();
+            assert_eq!(numeric.is_ok(), op != "~", "pid {} 42", op);
+            let text = format!("user{}www", op).parse::<Filter>();
+            assert_eq!(text.is_ok(), matches!(op, "==" | "=" | "!=" | "~"), "user{}www", op);
+        }
+    }
+}
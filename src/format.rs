@@ -0,0 +1,81 @@
+//! Plain-text renderings shared by the TUI and batch mode.
+
+use os_project::Snapshot;
+
+pub fn format_system_stats(snapshot: &Snapshot) -> String {
+    let stats = &snapshot.system;
+    let mem = &stats.memory;
+
+    let (rx, tx) = stats.network_throughput();
+    let network_usage = format!(
+        "Network: RX {}/s, TX {}/s ({} interfaces)",
+        format_bytes(rx),
+        format_bytes(tx),
+        stats.network.len()
+    );
+    let cpu_speed_str = stats
+        .cpu_speeds
+        .chunks(10)
+        .enumerate()
+        .map(|(chunk_index, chunk)| {
+            chunk
+                .iter()
+                .enumerate()
+                .map(|(i, speed)| format!("CPU {}: {:.1} MHz", chunk_index * 10 + i + 1, speed))
+                .collect::<Vec<String>>()
+                .join(", ")
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let cpu = &stats.cpu.total;
+    let cpu_usage = format!(
+        "%Cpu(s): {:>5.1} us, {:>5.1} sy, {:>5.1} ni, {:>5.1} id, {:>5.1} wa, {:>5.1} hi, {:>5.1} si, {:>5.1} st",
+        cpu.user, cpu.system, cpu.nice, cpu.idle, cpu.iowait, cpu.irq, cpu.softirq, cpu.steal
+    );
+
+    let (read, write) = stats.disk_throughput();
+    let disk_usage = format!(
+        "Disk: Read {}/s, Write {}/s ({} devices)",
+        format_bytes(read),
+        format_bytes(write),
+        stats.disks.len()
+    );
+
+    format!(
+        "this - {}  up {} seconds,  load average: {:.2}, {:.2}, {:.2}\n\
+        {}\n\
+        MiB Mem : {:>8.1} total, {:>8.1} used, {:>8.1} free, {:>8.1} buff/cache\n\
+        MiB Swap: {:>8.1} total, {:>8.1} used, {:>8.1} free, {:>8.1} avail Mem\n\
+        {}\n{}\n{}",
+        snapshot.time.format("%H:%M:%S"),
+        snapshot.uptime,
+        stats.load_avg.one,
+        stats.load_avg.five,
+        stats.load_avg.fifteen,
+        cpu_usage,
+        mem.total_mb,
+        mem.used_mb,
+        mem.free_mb,
+        mem.buffers_mb + mem.cached_mb,
+        mem.swap_total_mb,
+        mem.swap_used_mb,
+        mem.swap_free_mb,
+        mem.available_mb,
+        network_usage,
+        cpu_speed_str,
+        disk_usage
+    )
+}
+
+/// Formats a byte count with a binary unit, e.g. `12.3 MB`.
+pub fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
@@ -0,0 +1,35 @@
+//! Data layer of the system monitor.
+//!
+//! Everything that samples `/proc` lives here so it can be embedded without
+//! the terminal front end. A [`Collector`] produces an owned [`Snapshot`] of
+//! the system stats, the process table and recent crash events each time it
+//! is asked to.
+
+pub mod collector;
+pub mod cpu;
+pub mod crash;
+pub mod detail;
+pub mod disk;
+pub mod files;
+pub mod filter;
+pub mod memory;
+pub mod metrics;
+pub mod network;
+pub mod process;
+pub mod record;
+pub mod sched;
+pub mod signal;
+pub mod sockets;
+pub mod source;
+pub mod system;
+
+pub use collector::{Collector, CollectorCommand, CollectorOptions, CollectorThread, Snapshot};
+pub use filter::Filter;
+pub use process::{CpuMode, Process, ProcessSelection, SortCriteria, ThreadInfo};
+pub use record::{Player, Recorder};
+pub use source::DataSource;
+pub use system::{SystemSampler, SystemStats};
+
+/// Error type returned by the collectors.
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+pub type Result<T> = std::result::Result<T, Error>;
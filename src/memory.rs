@@ -0,0 +1,95 @@
+use crate::source::DataSource;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead};
+
+/// One line of /proc/meminfo.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MeminfoEntry {
+    pub name: String,
+    pub value: u64,
+    /// Whether `value` is in KiB; the `HugePages_*` lines are page counts.
+    pub is_kb: bool,
+}
+
+/// Memory figures in MiB, with the same meaning as in `free` and `top`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryStats {
+    pub total_mb: f64,
+    pub free_mb: f64,
+    /// Estimate of memory available for new work without swapping.
+    pub available_mb: f64,
+    pub buffers_mb: f64,
+    /// Page cache plus reclaimable slab, i.e. `Cached + SReclaimable`.
+    pub cached_mb: f64,
+    pub used_mb: f64,
+    pub shared_mb: f64,
+    pub slab_mb: f64,
+    pub swap_total_mb: f64,
+    pub swap_free_mb: f64,
+    pub swap_used_mb: f64,
+    pub huge_pages_total: u64,
+    pub huge_pages_free: u64,
+    pub huge_page_size_kb: u64,
+    /// Every line of /proc/meminfo, in file order.
+    pub entries: Vec<MeminfoEntry>,
+}
+
+impl MemoryStats {
+    pub fn collect(source: &DataSource) -> io::Result<MemoryStats> {
+        let reader = io::BufReader::new(source.open_proc("meminfo")?);
+
+        let mut entries = Vec::new();
+        for line in reader.lines().map_while(Result::ok) {
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let mut fields = value.split_whitespace();
+            let value = fields.next().and_then(|v| v.parse().ok()).unwrap_or(0);
+            entries.push(MeminfoEntry {
+                name: name.to_string(),
+                value,
+                is_kb: fields.next() == Some("kB"),
+            });
+        }
+
+        Ok(MemoryStats::from_entries(entries))
+    }
+
+    /// Derives the summary figures from the /proc/meminfo lines.
+    pub(crate) fn from_entries(entries: Vec<MeminfoEntry>) -> MemoryStats {
+        let get = |name: &str| entries.iter().find(|e| e.name == name).map(|e| e.value);
+        let mb = |name: &str| get(name).unwrap_or(0) as f64 / 1024.0;
+
+        let total_mb = mb("MemTotal");
+        let free_mb = mb("MemFree");
+        let buffers_mb = mb("Buffers");
+        let cached_mb = mb("Cached") + mb("SReclaimable");
+        // Kernels before 3.14 have no MemAvailable; fall back to the old
+        // free + buff/cache estimate like procps does
+        let available_mb = match get("MemAvailable") {
+            Some(kb) => kb as f64 / 1024.0,
+            None => free_mb + buffers_mb + cached_mb,
+        };
+        let swap_total_mb = mb("SwapTotal");
+        let swap_free_mb = mb("SwapFree");
+
+        MemoryStats {
+            total_mb,
+            free_mb,
+            available_mb,
+            buffers_mb,
+            cached_mb,
+            used_mb: (total_mb - available_mb).max(0.0),
+            shared_mb: mb("Shmem"),
+            slab_mb: mb("Slab"),
+            swap_total_mb,
+            swap_free_mb,
+            swap_used_mb: (swap_total_mb - swap_free_mb).max(0.0),
+            huge_pages_total: get("HugePages_Total").unwrap_or(0),
+            huge_pages_free: get("HugePages_Free").unwrap_or(0),
+            huge_page_size_kb: get("Hugepagesize").unwrap_or(0),
+            entries,
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Prometheus text exposition of a [`Snapshot`].
+
+use crate::collector::Snapshot;
+use crate::cpu::CpuUsage;
+use crate::Process;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+const PREFIX: &str = "os_project";
+
+/// Renders `snapshot` in the Prometheus text format.
+///
+/// Per-process series are limited to the `top_processes` heaviest processes
+/// by CPU and by resident memory, to keep the number of series bounded on
+/// busy hosts.
+pub fn render(snapshot: &Snapshot, top_processes: usize) -> String {
+    let mut out = Metrics::default();
+    let system = &snapshot.system;
+
+    out.family("uptime_seconds", "gauge", "Seconds since boot.");
+    out.sample::<&str>("uptime_seconds", &[], snapshot.uptime as f64);
+
+    out.family("load_average", "gauge", "System load average.");
+    for (period, value) in [
+        ("1m", system.load_avg.one),
+        ("5m", system.load_avg.five),
+        ("15m", system.load_avg.fifteen),
+    ] {
+        // /proc/loadavg has two decimals; avoid printing f32 rounding noise
+        let value = (value as f64 * 100.0).round() / 100.0;
+        out.sample("load_average", &[("period", period)], value);
+    }
+
+    out.family("memory_bytes", "gauge", "Memory figures from /proc/meminfo.");
+    let mem = &system.memory;
+    for (kind, mb) in [
+        ("total", mem.total_mb),
+        ("used", mem.used_mb),
+        ("free", mem.free_mb),
+        ("available", mem.available_mb),
+        ("buffers", mem.buffers_mb),
+        ("cached", mem.cached_mb),
+        ("shared", mem.shared_mb),
+        ("slab", mem.slab_mb),
+        ("swap_total", mem.swap_total_mb),
+        ("swap_used", mem.swap_used_mb),
+        ("swap_free", mem.swap_free_mb),
+    ] {
+        out.sample("memory_bytes", &[("kind", kind)], mb * 1024.0 * 1024.0);
+    }
+
+    out.family("cpu_usage_percent", "gauge", "Share of time spent in each CPU mode over the last interval.");
+    cpu_samples(&mut out, "total", &system.cpu.total);
+    for (i, core) in system.cpu.cores.iter().enumerate() {
+        cpu_samples(&mut out, &i.to_string(), core);
+    }
+
+    out.family("network_receive_bytes_total", "counter", "Bytes received since boot.");
+    for iface in &system.network {
+        out.sample("network_receive_bytes_total", &[("interface", iface.name.as_str())], iface.rx_bytes_total as f64);
+    }
+    out.family("network_transmit_bytes_total", "counter", "Bytes transmitted since boot.");
+    for iface in &system.network {
+        out.sample("network_transmit_bytes_total", &[("interface", iface.name.as_str())], iface.tx_bytes_total as f64);
+    }
+    out.family("network_rate_per_second", "gauge", "Per-interface rates over the last interval.");
+    for iface in &system.network {
+        for (direction, metric, value) in [
+            ("receive", "bytes", iface.rx_bytes_per_sec),
+            ("transmit", "bytes", iface.tx_bytes_per_sec),
+            ("receive", "packets", iface.rx_packets_per_sec),
+            ("transmit", "packets", iface.tx_packets_per_sec),
+            ("receive", "errors", iface.rx_errors_per_sec),
+            ("transmit", "errors", iface.tx_errors_per_sec),
+            ("receive", "drops", iface.rx_drops_per_sec),
+            ("transmit", "drops", iface.tx_drops_per_sec),
+        ] {
+            out.sample(
+                "network_rate_per_second",
+                &[("interface", iface.name.as_str()), ("direction", direction), ("metric", metric)],
+                value,
+            );
+        }
+    }
+
+    out.family("disk_read_bytes_total", "counter", "Bytes read since boot.");
+    for disk in &system.disks {
+        out.sample("disk_read_bytes_total", &[("device", disk.name.as_str())], disk.read_bytes_total as f64);
+    }
+    out.family("disk_written_bytes_total", "counter", "Bytes written since boot.");
+    for disk in &system.disks {
+        out.sample("disk_written_bytes_total", &[("device", disk.name.as_str())], disk.write_bytes_total as f64);
+    }
+    out.family("disk_rate_per_second", "gauge", "Per-device rates over the last interval.");
+    for disk in &system.disks {
+        for (metric, value) in [
+            ("read_bytes", disk.read_bytes_per_sec),
+            ("written_bytes", disk.write_bytes_per_sec),
+            ("reads", disk.read_iops),
+            ("writes", disk.write_iops),
+        ] {
+            out.sample("disk_rate_per_second", &[("device", disk.name.as_str()), ("metric", metric)], value);
+        }
+    }
+    out.family("disk_await_seconds", "gauge", "Average time an I/O request took over the last interval.");
+    for disk in &system.disks {
+        out.sample("disk_await_seconds", &[("device", disk.name.as_str())], disk.await_ms / 1000.0);
+    }
+    out.family("disk_utilisation_ratio", "gauge", "Share of the last interval the device was busy.");
+    for disk in &system.disks {
+        out.sample("disk_utilisation_ratio", &[("device", disk.name.as_str())], disk.util_percent / 100.0);
+    }
+
+    out.family("processes", "gauge", "Number of processes.");
+    out.sample::<&str>("processes", &[], snapshot.processes.len() as f64);
+
+    let exported = top_processes_by_cpu_and_memory(&snapshot.processes, top_processes);
+    out.family("process_cpu_percent", "gauge", "CPU usage of the heaviest processes.");
+    for p in &exported {
+        out.sample("process_cpu_percent", &process_labels(p), p.cpu_usage);
+    }
+    out.family("process_resident_bytes", "gauge", "Resident memory of the heaviest processes.");
+    for p in &exported {
+        out.sample("process_resident_bytes", &process_labels(p), p.mem_usage * 1024.0 * 1024.0);
+    }
+
+    out.text
+}
+
+fn cpu_samples(out: &mut Metrics, cpu: &str, usage: &CpuUsage) {
+    for (mode, value) in [
+        ("user", usage.user),
+        ("nice", usage.nice),
+        ("system", usage.system),
+        ("idle", usage.idle),
+        ("iowait", usage.iowait),
+        ("irq", usage.irq),
+        ("softirq", usage.softirq),
+        ("steal", usage.steal),
+    ] {
+        out.sample("cpu_usage_percent", &[("cpu", cpu), ("mode", mode)], value);
+    }
+}
+
+fn top_processes_by_cpu_and_memory(processes: &[Process], n: usize) -> Vec<&Process> {
+    let mut by_cpu: Vec<&Process> = processes.iter().collect();
+    by_cpu.sort_by(|a, b| b.cpu_usage.partial_cmp(&a.cpu_usage).unwrap_or(Ordering::Equal));
+    let mut by_mem = by_cpu.clone();
+    by_mem.sort_by(|a, b| b.mem_usage.partial_cmp(&a.mem_usage).unwrap_or(Ordering::Equal));
+
+    let mut seen = HashSet::new();
+    by_cpu
+        .into_iter()
+        .take(n)
+        .chain(by_mem.into_iter().take(n))
+        .filter(|p| seen.insert(p.pid))
+        .collect()
+}
+
+fn process_labels(p: &Process) -> [(&'static str, String); 3] {
+    [
+        ("pid", p.pid.to_string()),
+        ("command", p.command.clone()),
+        ("user", p.user.clone()),
+    ]
+}
+
+#[derive(Default)]
+struct Metrics {
+    text: String,
+}
+
+impl Metrics {
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.text, "# HELP {}_{} {}", PREFIX, name, help);
+        let _ = writeln!(self.text, "# TYPE {}_{} {}", PREFIX, name, kind);
+    }
+
+    fn sample<L: AsRef<str>>(&mut self, name: &str, labels: &[(&str, L)], value: f64) {
+        let _ = write!(self.text, "{}_{}", PREFIX, name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value.as_ref())))
+                .collect();
+            let _ = write!(self.text, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.text, " {}", value);
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,148 @@
+use crate::source::DataSource;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::Path;
+use std::time::Instant;
+
+/// Cumulative counters of one interface from /proc/net/dev.
+#[derive(Clone, Copy, Debug, Default)]
+struct Counters {
+    rx_bytes: u64,
+    rx_packets: u64,
+    rx_errors: u64,
+    rx_drops: u64,
+    tx_bytes: u64,
+    tx_packets: u64,
+    tx_errors: u64,
+    tx_drops: u64,
+}
+
+/// Per-interface rates over the last refresh interval.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct InterfaceStats {
+    pub name: String,
+    /// `operstate` from /sys/class/net, e.g. "up", "down" or "unknown".
+    pub operstate: String,
+    /// Link speed in Mbit/s, if the driver reports one.
+    pub speed_mbps: Option<u64>,
+    pub is_loopback: bool,
+    pub is_virtual: bool,
+    pub rx_bytes_per_sec: f64,
+    pub tx_bytes_per_sec: f64,
+    pub rx_packets_per_sec: f64,
+    pub tx_packets_per_sec: f64,
+    pub rx_errors_per_sec: f64,
+    pub tx_errors_per_sec: f64,
+    pub rx_drops_per_sec: f64,
+    pub tx_drops_per_sec: f64,
+    pub rx_bytes_total: u64,
+    pub tx_bytes_total: u64,
+}
+
+/// Diffs successive /proc/net/dev samples. Rates are zero on the first
+/// sample and for interfaces that just appeared.
+#[derive(Default)]
+pub struct NetworkSampler {
+    previous: HashMap<String, Counters>,
+    last_sample: Option<Instant>,
+}
+
+impl NetworkSampler {
+    /// Samples every interface, leaving out loopback and virtual ones
+    /// (bridges, veth, tun, ...) when `exclude_virtual` is set.
+    pub fn sample(&mut self, source: &DataSource, exclude_virtual: bool) -> io::Result<Vec<InterfaceStats>> {
+        let counters = read_net_dev(source)?;
+        let now = Instant::now();
+        let elapsed = self
+            .last_sample
+            .map(|last| now.duration_since(last).as_secs_f64())
+            .unwrap_or(0.0);
+
+        let mut interfaces = Vec::new();
+        for (name, current) in &counters {
+            let sys_dir = source.sys_path("class/net").join(name);
+            let is_loopback = read_sys(&sys_dir.join("type")).as_deref() == Some("772");
+            let is_virtual = source.sys_path("devices/virtual/net").join(name).exists();
+            if exclude_virtual && (is_loopback || is_virtual) {
+                continue;
+            }
+
+            let previous = self.previous.get(name).copied().unwrap_or(*current);
+            let rate = |now: u64, before: u64| {
+                if elapsed > 0.0 {
+                    now.saturating_sub(before) as f64 / elapsed
+                } else {
+                    0.0
+                }
+            };
+
+            interfaces.push(InterfaceStats {
+                name: name.clone(),
+                operstate: read_sys(&sys_dir.join("operstate")).unwrap_or_else(|| "unknown".to_string()),
+                speed_mbps: read_sys(&sys_dir.join("speed")).and_then(|v| v.parse().ok()),
+                is_loopback,
+                is_virtual,
+                rx_bytes_per_sec: rate(current.rx_bytes, previous.rx_bytes),
+                tx_bytes_per_sec: rate(current.tx_bytes, previous.tx_bytes),
+                rx_packets_per_sec: rate(current.rx_packets, previous.rx_packets),
+                tx_packets_per_sec: rate(current.tx_packets, previous.tx_packets),
+                rx_errors_per_sec: rate(current.rx_errors, previous.rx_errors),
+                tx_errors_per_sec: rate(current.tx_errors, previous.tx_errors),
+                rx_drops_per_sec: rate(current.rx_drops, previous.rx_drops),
+                tx_drops_per_sec: rate(current.tx_drops, previous.tx_drops),
+                rx_bytes_total: current.rx_bytes,
+                tx_bytes_total: current.tx_bytes,
+            });
+        }
+
+        self.previous = counters.into_iter().collect();
+        self.last_sample = Some(now);
+        Ok(interfaces)
+    }
+}
+
+fn read_net_dev(source: &DataSource) -> io::Result<Vec<(String, Counters)>> {
+    let file = source.open_proc("net/dev")?;
+    let reader = io::BufReader::new(file);
+
+    let mut interfaces = Vec::new();
+
+    for line in reader.lines().skip(2).map_while(Result::ok) {
+        // The name and the first counter are not always separated by a space
+        let Some((name, counters)) = line.split_once(':') else {
+            continue;
+        };
+        let fields: Vec<u64> = counters
+            .split_whitespace()
+            .map(|v| v.parse().unwrap_or(0))
+            .collect();
+        if fields.len() > 11 {
+            interfaces.push((
+                name.trim().to_string(),
+                Counters {
+                    rx_bytes: fields[0],
+                    rx_packets: fields[1],
+                    rx_errors: fields[2],
+                    rx_drops: fields[3],
+                    tx_bytes: fields[8],
+                    tx_packets: fields[9],
+                    tx_errors: fields[10],
+                    tx_drops: fields[11],
+                },
+            ));
+        }
+    }
+
+    Ok(interfaces)
+}
+
+// Reading speed of a link that is down fails with EINVAL, so errors are
+// treated as "not reported"; a negative speed means the same.
+fn read_sys(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty() && !v.starts_with('-'))
+}
@@ -0,0 +1,469 @@
+use crate::source::DataSource;
+use procfs::process::{Stat, Status};
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+use std::str::FromStr;
+use std::time::Instant;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SortCriteria {
+    CPU,
+    Memory,
+    PID,
+    PR,
+    Virtual,
+    Shared,
+    Swap,
+    MemPercent,
+    Pss,
+    Uss,
+}
+
+impl SortCriteria {
+    /// Whether sorting by this needs PSS/USS from smaps_rollup.
+    pub fn needs_smaps(self) -> bool {
+        matches!(self, SortCriteria::Pss | SortCriteria::Uss)
+    }
+}
+
+impl FromStr for SortCriteria {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<SortCriteria, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "cpu" => Ok(SortCriteria::CPU),
+            "mem" | "memory" => Ok(SortCriteria::Memory),
+            "pid" => Ok(SortCriteria::PID),
+            "pr" | "priority" => Ok(SortCriteria::PR),
+            "virt" | "virtual" => Ok(SortCriteria::Virtual),
+            "shr" | "shared" => Ok(SortCriteria::Shared),
+            "swap" => Ok(SortCriteria::Swap),
+            "%mem" | "memp" => Ok(SortCriteria::MemPercent),
+            "pss" => Ok(SortCriteria::Pss),
+            "uss" => Ok(SortCriteria::Uss),
+            _ => Err(format!(
+                "unknown sort column '{}' (expected cpu, mem, pid, pr, virt, shr, swap, %mem, pss or uss)",
+                s
+            )),
+        }
+    }
+}
+
+/// How per-process CPU% is scaled on multi-core machines.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum CpuMode {
+    /// Percent of one core, so a process can exceed 100% (top's default).
+    #[default]
+    Irix,
+    /// Percent of the whole machine, divided by the number of cores.
+    Solaris,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Process {
+    pub pid: i32,
+    pub ppid: i32,
+    pub uid: u32,
+    pub user: String,
+    pub state: char,
+    pub threads: i64,
+    pub priority: i64,
+    #[serde(default)]
+    pub nice: i64,
+    /// Scheduling policy number from /proc/PID/stat; see [`crate::sched::Policy`].
+    #[serde(default)]
+    pub policy: u32,
+    /// Real-time priority, 1-99 under SCHED_FIFO and SCHED_RR, else 0.
+    #[serde(default)]
+    pub rt_priority: u32,
+    /// CPUs the process may run on, as `Cpus_allowed_list` writes them
+    /// (`0-3,6`).
+    #[serde(default)]
+    pub cpus_allowed: String,
+    pub cpu_usage: f64,
+    /// Resident memory in MiB.
+    #[serde(default)]
+    pub mem_usage: f64,
+    /// Virtual size in KiB.
+    #[serde(default)]
+    pub virt_kb: u64,
+    /// Resident set size in KiB.
+    #[serde(default)]
+    pub res_kb: u64,
+    /// Resident memory backed by files or shared memory, in KiB.
+    #[serde(default)]
+    pub shr_kb: u64,
+    /// Swapped-out memory (`VmSwap`) in KiB.
+    #[serde(default)]
+    pub swap_kb: u64,
+    /// Resident memory as a share of total RAM.
+    #[serde(default)]
+    pub mem_percent: f64,
+    /// Proportional set size from smaps_rollup, in KiB. Only read when
+    /// asked for, and `None` if the file is not readable.
+    #[serde(default)]
+    pub pss_kb: Option<u64>,
+    /// Unique set size (private clean + dirty pages), in KiB.
+    #[serde(default)]
+    pub uss_kb: Option<u64>,
+    pub time_plus: String,
+    /// Name from /proc/PID/stat, cut to 15 characters by the kernel.
+    pub command: String,
+    /// Full argument list from /proc/PID/cmdline, or `[comm]` for kernel
+    /// threads and processes whose arguments can't be read.
+    #[serde(default)]
+    pub cmdline: String,
+    /// Resolved /proc/PID/exe link, if readable.
+    #[serde(default)]
+    pub exe: Option<String>,
+    /// Threads from /proc/PID/task; only read when asked for.
+    #[serde(default)]
+    pub tasks: Vec<ThreadInfo>,
+}
+
+impl Process {
+    pub(crate) fn from_stat(stat: Stat, status: &Status, user: String, cpu_usage: f64) -> Process {
+        Process {
+            pid: stat.pid,
+            ppid: stat.ppid,
+            uid: status.ruid,
+            user,
+            state: stat.state,
+            threads: stat.num_threads,
+            priority: stat.priority,
+            nice: stat.nice,
+            policy: stat.policy.unwrap_or(0),
+            rt_priority: stat.rt_priority.unwrap_or(0),
+            cpus_allowed: status
+                .cpus_allowed_list
+                .as_ref()
+                .map(|ranges| format_cpu_list(ranges))
+                .unwrap_or_default(),
+            cpu_usage,
+            // VmRSS rather than stat's page count, which would need the page
+            // size of the machine the /proc tree came from
+            mem_usage: status.vmrss.unwrap_or(0) as f64 / 1024.0,
+            virt_kb: stat.vsize / 1024,
+            res_kb: status.vmrss.unwrap_or(0),
+            // Same as top's SHR: file-backed plus shared memory pages
+            shr_kb: status.rssfile.unwrap_or(0) + status.rssshmem.unwrap_or(0),
+            swap_kb: status.vmswap.unwrap_or(0),
+            mem_percent: 0.0,
+            pss_kb: None,
+            uss_kb: None,
+            time_plus: format_time(stat.utime + stat.stime),
+            cmdline: format!("[{}]", stat.comm),
+            exe: None,
+            tasks: Vec::new(),
+            command: stat.comm,
+        }
+    }
+}
+
+fn format_cpu_list(ranges: &[(u32, u32)]) -> String {
+    ranges
+        .iter()
+        .map(|&(first, last)| if first == last { first.to_string() } else { format!("{}-{}", first, last) })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Remembers each PID's CPU ticks between samples so CPU% can be computed
+/// over the refresh interval instead of the whole process lifetime.
+#[derive(Default)]
+pub struct CpuTracker {
+    pub mode: CpuMode,
+    // pid -> (starttime, utime + stime); starttime tells a reused PID apart
+    previous: HashMap<i32, (u64, u64)>,
+    current: HashMap<i32, (u64, u64)>,
+    last_sample: Option<Instant>,
+    elapsed: f64,
+    // CPUs of the sampled system, to cap and scale usage by
+    cpus: u64,
+}
+
+impl CpuTracker {
+    pub fn new(mode: CpuMode) -> CpuTracker {
+        CpuTracker {
+            mode,
+            ..CpuTracker::default()
+        }
+    }
+
+    /// Starts a new sample; must be called once before the `usage` calls of
+    /// a refresh.
+    fn begin(&mut self, source: &DataSource) {
+        self.cpus = cpu_count(source);
+        let now = Instant::now();
+        self.elapsed = self
+            .last_sample
+            .map(|last| now.duration_since(last).as_secs_f64())
+            .unwrap_or(0.0);
+        self.last_sample = Some(now);
+        self.previous = std::mem::take(&mut self.current);
+    }
+
+    fn usage(&mut self, stat: &Stat, uptime: f64) -> f64 {
+        let ticks = stat.utime + stat.stime;
+        self.current.insert(stat.pid, (stat.starttime, ticks));
+
+        let usage = if self.elapsed > 0.0 {
+            let previous = match self.previous.get(&stat.pid) {
+                Some(&(starttime, previous)) if starttime == stat.starttime => previous,
+                // Started (or PID reused) since the last sample
+                _ => 0,
+            };
+            let hertz = procfs::ticks_per_second().unwrap_or(100) as f64;
+            (ticks.saturating_sub(previous) as f64 / hertz) / self.elapsed * 100.0
+        } else {
+            // Nothing to diff against on the first sample
+            calculate_cpu_usage(stat, uptime)
+        }
+        .min(100.0 * self.cpus as f64);
+
+        match self.mode {
+            CpuMode::Irix => usage,
+            CpuMode::Solaris => usage / self.cpus as f64,
+        }
+    }
+}
+
+/// Restricts the process table to some PIDs and/or one user, like
+/// `top -p` and `top -u`. An empty selection matches everything.
+#[derive(Clone, Debug, Default)]
+pub struct ProcessSelection {
+    pub pids: Vec<i32>,
+    /// User name or numeric UID.
+    pub user: Option<String>,
+}
+
+impl ProcessSelection {
+    pub fn matches(&self, process: &Process) -> bool {
+        let pid_matches = self.pids.is_empty() || self.pids.contains(&process.pid);
+        let user_matches = match &self.user {
+            Some(user) => *user == process.user || *user == process.uid.to_string(),
+            None => true,
+        };
+        pid_matches && user_matches
+    }
+}
+
+/// Reads every process from procfs, skipping the ones that vanish mid-read.
+///
+/// PSS and USS are only filled in when `read_smaps` is set, as reading
+/// smaps_rollup makes the kernel walk every mapping of every process.
+pub fn collect_processes(
+    source: &DataSource,
+    cpu: &mut CpuTracker,
+    uptime: f64,
+    read_smaps: bool,
+) -> crate::Result<Vec<Process>> {
+    cpu.begin(source);
+    let mut processes = Vec::new();
+    for proc in procfs::process::all_processes_with_root(source.proc_root())?.flatten() {
+        if let (Ok(stat), Ok(status)) = (proc.stat(), proc.status()) {
+            let cpu_usage = cpu.usage(&stat, uptime);
+            let user = source.user_name(status.ruid);
+            let mut process = Process::from_stat(stat, &status, user, cpu_usage);
+            if let Some(args) = proc.cmdline().ok().filter(|args| !args.is_empty()) {
+                process.cmdline = args.join(" ");
+            }
+            process.exe = proc.exe().ok().map(|path| path.display().to_string());
+            if read_smaps {
+                if let Ok(rollup) = proc.smaps_rollup() {
+                    // smaps_rollup values are parsed into bytes
+                    let map = &rollup.memory_map_data.map;
+                    let kb = |key: &str| map.get(key).copied().unwrap_or(0) / 1024;
+                    process.pss_kb = Some(kb("Pss"));
+                    process.uss_kb = Some(kb("Private_Clean") + kb("Private_Dirty"));
+                }
+            }
+            processes.push(process);
+        }
+    }
+    Ok(processes)
+}
+
+/// One thread of a process, from /proc/PID/task/TID.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ThreadInfo {
+    pub tid: i32,
+    pub name: String,
+    pub state: char,
+    pub cpu_usage: f64,
+    /// CPU the thread last ran on.
+    pub processor: Option<i32>,
+    pub voluntary_ctxt_switches: u64,
+    pub nonvoluntary_ctxt_switches: u64,
+}
+
+/// Fills in `tasks` for the processes `wanted` picks. `cpu` tracks the
+/// threads' CPU time between refreshes and must not be the process tracker,
+/// as a main thread's TID is its process's PID.
+pub fn collect_threads(
+    source: &DataSource,
+    cpu: &mut CpuTracker,
+    processes: &mut [Process],
+    uptime: f64,
+    wanted: impl Fn(&Process) -> bool,
+) {
+    cpu.begin(source);
+    for process in processes.iter_mut().filter(|p| wanted(p)) {
+        let path = source.proc_path(process.pid.to_string());
+        let Ok(tasks) = procfs::process::Process::new_with_root(path).and_then(|p| p.tasks()) else {
+            continue;
+        };
+        process.tasks = tasks
+            .flatten()
+            .filter_map(|task| {
+                let stat = task.stat().ok()?;
+                let status = task.status().ok();
+                Some(ThreadInfo {
+                    tid: task.tid,
+                    state: stat.state,
+                    cpu_usage: cpu.usage(&stat, uptime),
+                    processor: stat.processor,
+                    voluntary_ctxt_switches: status.as_ref().and_then(|s| s.voluntary_ctxt_switches).unwrap_or(0),
+                    nonvoluntary_ctxt_switches: status.and_then(|s| s.nonvoluntary_ctxt_switches).unwrap_or(0),
+                    name: stat.comm,
+                })
+            })
+            .collect();
+    }
+}
+
+/// `pid` and all of its descendants, each child before its parent. Read
+/// fresh from /proc so anything forked since the last refresh is included.
+pub fn subtree_bottom_up(source: &DataSource, pid: i32) -> crate::Result<Vec<i32>> {
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    for proc in procfs::process::all_processes_with_root(source.proc_root())?.flatten() {
+        if let Ok(stat) = proc.stat() {
+            children.entry(stat.ppid).or_default().push(stat.pid);
+        }
+    }
+    let mut order = Vec::new();
+    push_subtree(&children, pid, &mut order);
+    Ok(order)
+}
+
+fn push_subtree(children: &HashMap<i32, Vec<i32>>, pid: i32, order: &mut Vec<i32>) {
+    for &child in children.get(&pid).into_iter().flatten() {
+        if child != pid {
+            push_subtree(children, child, order);
+        }
+    }
+    order.push(pid);
+}
+
+/// Whether `pid` exists and has not exited; a zombie waiting to be reaped
+/// counts as gone.
+pub fn is_running(source: &DataSource, pid: i32) -> bool {
+    procfs::process::Process::new_with_root(source.proc_path(pid.to_string()))
+        .and_then(|proc| proc.stat())
+        .is_ok_and(|stat| !matches!(stat.state, 'Z' | 'X'))
+}
+
+/// Fills in `mem_percent` from each process's resident size.
+pub(crate) fn set_mem_percent(processes: &mut [Process], total_mb: f64) {
+    let total_kb = total_mb * 1024.0;
+    if total_kb > 0.0 {
+        for p in processes.iter_mut() {
+            p.mem_percent = p.res_kb as f64 * 100.0 / total_kb;
+        }
+    }
+}
+
+pub fn sort_processes(processes: &mut [Process], sort_criteria: SortCriteria) {
+    match sort_criteria {
+        SortCriteria::CPU => {
+            processes.sort_by(|a, b| {
+                b.cpu_usage.partial_cmp(&a.cpu_usage).unwrap_or(Ordering::Equal)
+            });
+        }
+        SortCriteria::Memory => {
+            processes.sort_by(|a, b| {
+                b.mem_usage.partial_cmp(&a.mem_usage).unwrap_or(Ordering::Equal)
+            });
+        }
+        SortCriteria::PID => {
+            processes.sort_by_key(|p| p.pid);
+        }
+        SortCriteria::PR => {
+            processes.sort_by_key(|p| Reverse(p.priority));
+        }
+        SortCriteria::Virtual => processes.sort_by_key(|p| Reverse(p.virt_kb)),
+        SortCriteria::Shared => processes.sort_by_key(|p| Reverse(p.shr_kb)),
+        SortCriteria::Swap => processes.sort_by_key(|p| Reverse(p.swap_kb)),
+        SortCriteria::MemPercent => {
+            processes.sort_by(|a, b| {
+                b.mem_percent.partial_cmp(&a.mem_percent).unwrap_or(Ordering::Equal)
+            });
+        }
+        SortCriteria::Pss => processes.sort_by_key(|p| Reverse(p.pss_kb)),
+        SortCriteria::Uss => processes.sort_by_key(|p| Reverse(p.uss_kb)),
+    }
+}
+
+/// Seconds since boot, from /proc/uptime.
+pub fn uptime(source: &DataSource) -> crate::Result<f64> {
+    Ok(procfs::Uptime::from_reader(source.open_proc("uptime")?)?.uptime)
+}
+
+fn calculate_cpu_usage(stat: &Stat, uptime: f64) -> f64 {
+    let total_time = stat.utime + stat.stime + (stat.cutime + stat.cstime) as u64;
+    let hertz = procfs::ticks_per_second().unwrap_or(100) as f64;
+    let elapsed_time = uptime - (stat.starttime as f64 / hertz);
+    if elapsed_time > 0.0 {
+        ((total_time as f64 / hertz) / elapsed_time) * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Online CPUs of the system `source` reads, from the `cpuN` lines of
+/// /proc/stat, so a captured tree is scaled by its own CPU count rather
+/// than the host's.
+fn cpu_count(source: &DataSource) -> u64 {
+    let Ok(stat) = source.open_proc("stat") else {
+        return 1;
+    };
+    let cpus = io::BufReader::new(stat)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| line.strip_prefix("cpu").is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit())))
+        .count();
+    cpus.max(1) as u64
+}
+
+pub fn format_time(clock_ticks: u64) -> String {
+    let seconds = clock_ticks as f64 / procfs::ticks_per_second().unwrap_or(100) as f64;
+    let minutes = (seconds as u64 / 60) % 60;
+    let hours = seconds as u64 / 3600;
+    let seconds = seconds as u64 % 60;
+    format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn cpu_count_comes_from_the_source() {
+        let root = std::env::temp_dir().join(format!("os_project-cpus-{}", std::process::id()));
+        fs::create_dir_all(root.join("proc")).unwrap();
+        fs::write(
+            root.join("proc/stat"),
+            "cpu  10 0 10 100 0 0 0 0 0 0\ncpu0 5 0 5 50 0 0 0 0 0 0\ncpu1 5 0 5 50 0 0 0 0 0 0\n\
+             cpu3 0 0 0 0 0 0 0 0 0 0\nintr 1 2 3\nctxt 100\n",
+        )
+        .unwrap();
+        let count = cpu_count(&DataSource::with_root(&root));
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(cpu_count(&DataSource::with_root("/nonexistent")), 1);
+    }
+}
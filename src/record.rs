@@ -0,0 +1,490 @@
+use crate::collector::Snapshot;
+use crate::memory::MemoryStats;
+use crate::process;
+use chrono::{DateTime, Duration, Local, NaiveDateTime, NaiveTime, TimeZone};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::mem;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+
+/// First bytes of every recording.
+const MAGIC: &[u8; 8] = b"OSPREC1\n";
+
+/// Payload length (u32), flags (u8) and time in Unix milliseconds (i64),
+/// all little-endian.
+const FRAME_HEADER_LEN: usize = 13;
+
+/// The frame carries `crash_events`; frames without it share the list of
+/// the last one that did.
+const FLAG_CRASH_EVENTS: u8 = 1;
+
+/// Appends snapshots to a recording.
+///
+/// Each snapshot is a frame: a small header, then the snapshot as
+/// deflate-compressed JSON. Fields that can be worked out again on load
+/// (memory summaries, `mem_usage`, `mem_percent`, a `[comm]` cmdline) are
+/// left out, and the crash log is only written when it changes.
+///
+/// Every frame is flushed as soon as it is written, so a recording cut
+/// short (the machine crashed, the monitor was killed) stays readable up to
+/// its last complete frame.
+pub struct Recorder {
+    writer: BufWriter<File>,
+    crash_events: Option<Vec<String>>,
+}
+
+impl Recorder {
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Recorder> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(MAGIC)?;
+        Ok(Recorder {
+            writer,
+            crash_events: None,
+        })
+    }
+
+    pub fn record(&mut self, snapshot: &Snapshot) -> crate::Result<()> {
+        let mut value = serde_json::to_value(snapshot)?;
+        strip_derived(&mut value);
+
+        let mut flags = 0;
+        if self.crash_events.as_ref() == Some(&snapshot.crash_events) {
+            if let Some(object) = value.as_object_mut() {
+                object.remove("crash_events");
+            }
+        } else {
+            flags |= FLAG_CRASH_EVENTS;
+            self.crash_events = Some(snapshot.crash_events.clone());
+        }
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        serde_json::to_writer(&mut encoder, &value)?;
+        let payload = encoder.finish()?;
+
+        self.writer.write_all(&(payload.len() as u32).to_le_bytes())?;
+        self.writer.write_all(&[flags])?;
+        self.writer.write_all(&snapshot.time.timestamp_millis().to_le_bytes())?;
+        self.writer.write_all(&payload)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Drops what [`fill_derived`] can rebuild.
+fn strip_derived(snapshot: &mut Value) {
+    if let Some(memory) = snapshot.pointer_mut("/system/memory") {
+        *memory = json!({ "entries": memory["entries"].take() });
+    }
+    if let Some(processes) = snapshot.get_mut("processes").and_then(Value::as_array_mut) {
+        for p in processes.iter_mut().filter_map(Value::as_object_mut) {
+            p.remove("mem_usage");
+            p.remove("mem_percent");
+            let kernel_name = p.get("command").and_then(Value::as_str).map(|comm| format!("[{}]", comm));
+            if kernel_name.is_some() && p.get("cmdline").and_then(Value::as_str) == kernel_name.as_deref() {
+                p.remove("cmdline");
+            }
+        }
+    }
+}
+
+fn fill_derived(snapshot: &mut Snapshot) {
+    let memory = &mut snapshot.system.memory;
+    *memory = MemoryStats::from_entries(mem::take(&mut memory.entries));
+    for p in snapshot.processes.iter_mut() {
+        p.mem_usage = p.res_kb as f64 / 1024.0;
+        if p.cmdline.is_empty() {
+            p.cmdline = format!("[{}]", p.command);
+        }
+    }
+    process::set_mem_percent(&mut snapshot.processes, snapshot.system.memory.total_mb);
+}
+
+/// Where a snapshot is in the recording file.
+struct Frame {
+    offset: u64,
+    len: usize,
+    time: DateTime<Local>,
+    /// The frame holding this snapshot's crash log; its own index if it
+    /// carries one.
+    crash_frame: usize,
+}
+
+/// Steps through the snapshots of a recording.
+///
+/// Only the frame headers are read up front; each snapshot is decoded when
+/// it is moved to.
+pub struct Player {
+    file: File,
+    frames: Vec<Frame>,
+    position: usize,
+    current: Snapshot,
+    pub paused: bool,
+}
+
+impl Player {
+    pub fn load(path: impl AsRef<Path>) -> crate::Result<Player> {
+        let file = File::open(path)?;
+        let frames = index_frames(&file)?;
+        if frames.is_empty() {
+            return Err("recording contains no snapshots".into());
+        }
+        let mut current = decode(&file, &frames[0]).map_err(|e| format!("snapshot 1: {}", e))?;
+        fill_derived(&mut current);
+
+        Ok(Player {
+            file,
+            frames,
+            position: 0,
+            current,
+            paused: false,
+        })
+    }
+
+    pub fn current(&self) -> &Snapshot {
+        &self.current
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.position + 1 == self.frames.len()
+    }
+
+    /// Moves to the snapshot at `position`, decoding it from the file.
+    pub fn go_to(&mut self, position: usize) -> crate::Result<()> {
+        if position == self.position {
+            return Ok(());
+        }
+        let frame = self.frames.get(position).ok_or("no such snapshot")?;
+        let read = || -> crate::Result<Snapshot> {
+            let mut snapshot = decode(&self.file, frame)?;
+            if frame.crash_frame != position {
+                snapshot.crash_events = if self.frames[self.position].crash_frame == frame.crash_frame {
+                    self.current.crash_events.clone()
+                } else {
+                    decode(&self.file, &self.frames[frame.crash_frame])?.crash_events
+                };
+            }
+            Ok(snapshot)
+        };
+        let mut snapshot = read().map_err(|e| format!("snapshot {}: {}", position + 1, e))?;
+        fill_derived(&mut snapshot);
+        self.current = snapshot;
+        self.position = position;
+        Ok(())
+    }
+
+    /// Moves to the next snapshot. Returns `false` at the end of the recording.
+    pub fn step_forward(&mut self) -> crate::Result<bool> {
+        if self.at_end() {
+            return Ok(false);
+        }
+        self.go_to(self.position + 1)?;
+        Ok(true)
+    }
+
+    /// Moves to the previous snapshot. Returns `false` at the start.
+    pub fn step_back(&mut self) -> crate::Result<bool> {
+        if self.position == 0 {
+            return Ok(false);
+        }
+        self.go_to(self.position - 1)?;
+        Ok(true)
+    }
+
+    /// Jumps to the first snapshot taken at or after `time`, given either as
+    /// a time of day (`HH:MM[:SS]`) or a full `YYYY-MM-DD HH:MM[:SS]`.
+    ///
+    /// A time of day means its next occurrence from the start of the
+    /// recording, so in one running from 22:00 to 06:00, `02:00` is the
+    /// morning after.
+    pub fn seek(&mut self, time: &str) -> crate::Result<()> {
+        let time = time.trim();
+        let date_time = if let Some(date_time) = parse_date_time(time) {
+            date_time
+        } else if let Some(time_of_day) = parse_time(time) {
+            let start = self.frames[0].time.naive_local();
+            let date_time = start.date().and_time(time_of_day);
+            if date_time < start {
+                date_time + Duration::days(1)
+            } else {
+                date_time
+            }
+        } else {
+            return Err(format!("'{}' is not a time (expected HH:MM[:SS])", time).into());
+        };
+        let date_time = Local
+            .from_local_datetime(&date_time)
+            .earliest()
+            .ok_or("no such local time")?;
+
+        match self.frames.iter().position(|frame| frame.time >= date_time) {
+            Some(position) => self.go_to(position),
+            None => Err(format!("no snapshot at or after {}", time).into()),
+        }
+    }
+}
+
+fn parse_time(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"))
+        .ok()
+}
+
+fn parse_date_time(s: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M"))
+        .ok()
+}
+
+/// Reads the frame headers, skipping over the payloads.
+fn index_frames(file: &File) -> crate::Result<Vec<Frame>> {
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+    let mut magic = [0; MAGIC.len()];
+    if reader.read_exact(&mut magic).is_err() || &magic != MAGIC {
+        return Err("not a recording".into());
+    }
+
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut offset = MAGIC.len() as u64;
+    let mut header = [0; FRAME_HEADER_LEN];
+    while reader.read_exact(&mut header).is_ok() {
+        let len = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+        let flags = header[4];
+        let millis = i64::from_le_bytes(header[5..13].try_into().unwrap());
+        offset += FRAME_HEADER_LEN as u64;
+        // The last frame of an interrupted recording may be cut off
+        if offset + len as u64 > file_len {
+            break;
+        }
+        let time = DateTime::from_timestamp_millis(millis)
+            .ok_or_else(|| format!("snapshot {}: bad time", frames.len() + 1))?;
+        let crash_frame = if flags & FLAG_CRASH_EVENTS != 0 {
+            frames.len()
+        } else {
+            frames.last().map_or(0, |frame| frame.crash_frame)
+        };
+        frames.push(Frame {
+            offset,
+            len,
+            time: time.with_timezone(&Local),
+            crash_frame,
+        });
+        reader.seek_relative(len as i64)?;
+        offset += len as u64;
+    }
+    Ok(frames)
+}
+
+fn decode(file: &File, frame: &Frame) -> crate::Result<Snapshot> {
+    let mut payload = vec![0; frame.len];
+    file.read_exact_at(&mut payload, frame.offset)?;
+    Ok(serde_json::from_reader(DeflateDecoder::new(payload.as_slice()))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MeminfoEntry;
+    use crate::process::Process;
+    use crate::system::SystemStats;
+    use std::fs::{self, OpenOptions};
+    use std::path::PathBuf;
+
+    /// A recording file removed again when the test ends.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> TempFile {
+            TempFile(std::env::temp_dir().join(format!("os_project-{}-{}.rec", std::process::id(), name)))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn process(pid: i32, command: &str, cmdline: &str) -> Process {
+        let mut process: Process = serde_json::from_value(json!({
+            "pid": pid,
+            "ppid": 1,
+            "uid": 0,
+            "user": "root",
+            "state": "S",
+            "threads": 1,
+            "priority": 20,
+            "cpu_usage": 0.0,
+            "res_kb": 2048,
+            "time_plus": "00:00:00",
+            "command": command,
+            "cmdline": cmdline,
+        }))
+        .unwrap();
+        process.mem_usage = 2.0;
+        process
+    }
+
+    fn snapshot(time: &str, crash_events: &[&str]) -> Snapshot {
+        let time = NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M").unwrap();
+        let entries = vec![MeminfoEntry {
+            name: "MemTotal".to_string(),
+            value: 4096,
+            is_kb: true,
+        }];
+        let mut processes = vec![process(1, "init", "/sbin/init splash"), process(2, "kthreadd", "[kthreadd]")];
+        process::set_mem_percent(&mut processes, 4.0);
+        Snapshot {
+            time: Local.from_local_datetime(&time).earliest().unwrap(),
+            uptime: 100,
+            system: SystemStats {
+                memory: MemoryStats::from_entries(entries),
+                ..SystemStats::default()
+            },
+            processes,
+            crash_events: crash_events.iter().map(|e| e.to_string()).collect(),
+            detail: None,
+            sockets: Vec::new(),
+        }
+    }
+
+    fn record(file: &TempFile, snapshots: &[Snapshot]) {
+        let mut recorder = Recorder::create(&file.0).unwrap();
+        for snapshot in snapshots {
+            recorder.record(snapshot).unwrap();
+        }
+    }
+
+    #[test]
+    fn derived_fields_are_left_out_and_rebuilt() {
+        let original = snapshot("2026-01-10 12:00", &[]);
+        let mut value = serde_json::to_value(&original).unwrap();
+        strip_derived(&mut value);
+        assert_eq!(value["system"]["memory"].as_object().unwrap().len(), 1);
+        assert!(value["processes"][0].get("mem_usage").is_none());
+        assert!(value["processes"][0].get("mem_percent").is_none());
+        assert_eq!(value["processes"][0]["cmdline"], "/sbin/init splash");
+        assert!(value["processes"][1].get("cmdline").is_none());
+
+        let mut restored: Snapshot = serde_json::from_value(value).unwrap();
+        fill_derived(&mut restored);
+        assert_eq!(restored.system.memory.total_mb, 4.0);
+        for (restored, original) in restored.processes.iter().zip(&original.processes) {
+            assert_eq!(restored.mem_usage, original.mem_usage);
+            assert_eq!(restored.mem_percent, 50.0);
+            assert_eq!(restored.cmdline, original.cmdline);
+        }
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        let file = TempFile::new("round-trip");
+        let snapshots = [snapshot("2026-01-10 12:00", &[]), snapshot("2026-01-10 12:01", &[])];
+        record(&file, &snapshots);
+
+        let mut player = Player::load(&file.0).unwrap();
+        assert_eq!(player.len(), 2);
+        assert_eq!(player.current().time, snapshots[0].time);
+        assert!(player.step_forward().unwrap());
+        assert!(!player.step_forward().unwrap());
+        assert_eq!(player.current().time, snapshots[1].time);
+        assert_eq!(player.current().processes[1].cmdline, "[kthreadd]");
+    }
+
+    #[test]
+    fn unchanged_crash_logs_are_written_once() {
+        let file = TempFile::new("crash-events");
+        record(
+            &file,
+            &[
+                snapshot("2026-01-10 12:00", &["oom: killed 42"]),
+                snapshot("2026-01-10 12:01", &["oom: killed 42"]),
+                snapshot("2026-01-10 12:02", &["oom: killed 42", "segfault in 43"]),
+                snapshot("2026-01-10 12:03", &["oom: killed 42", "segfault in 43"]),
+            ],
+        );
+
+        let mut player = Player::load(&file.0).unwrap();
+        let crash_frames: Vec<usize> = player.frames.iter().map(|f| f.crash_frame).collect();
+        assert_eq!(crash_frames, [0, 0, 2, 2]);
+        player.go_to(3).unwrap();
+        assert_eq!(player.current().crash_events.len(), 2);
+        // Back across a change, so the list is decoded from frame 0
+        player.go_to(1).unwrap();
+        assert_eq!(player.current().crash_events, ["oom: killed 42"]);
+    }
+
+    #[test]
+    fn a_cut_off_last_frame_is_skipped() {
+        let file = TempFile::new("truncated");
+        let snapshots: Vec<Snapshot> = (0..3)
+            .map(|i| snapshot(&format!("2026-01-10 12:0{}", i), &[]))
+            .collect();
+        record(&file, &snapshots);
+        let len = fs::metadata(&file.0).unwrap().len();
+
+        // Mid-payload, then mid-header of the last frame
+        let last_frame = Player::load(&file.0).unwrap().frames[2].offset - FRAME_HEADER_LEN as u64;
+        for cut in [len - 10, last_frame + 5] {
+            OpenOptions::new().write(true).open(&file.0).unwrap().set_len(cut).unwrap();
+            let mut player = Player::load(&file.0).unwrap();
+            assert_eq!(player.len(), 2);
+            player.go_to(1).unwrap();
+            assert_eq!(player.current().time, snapshots[1].time);
+        }
+    }
+
+    #[test]
+    fn other_files_are_rejected() {
+        let file = TempFile::new("not-a-recording");
+        fs::write(&file.0, "{\"time\": 0}\n").unwrap();
+        assert_eq!(Player::load(&file.0).err().unwrap().to_string(), "not a recording");
+        record(&file, &[]);
+        assert_eq!(Player::load(&file.0).err().unwrap().to_string(), "recording contains no snapshots");
+    }
+
+    #[test]
+    fn seek_by_time_of_day_rolls_past_midnight() {
+        let file = TempFile::new("seek");
+        record(
+            &file,
+            &[
+                snapshot("2026-01-10 22:00", &[]),
+                snapshot("2026-01-11 02:00", &[]),
+                snapshot("2026-01-11 06:00", &[]),
+            ],
+        );
+
+        let mut player = Player::load(&file.0).unwrap();
+        player.seek("02:00").unwrap();
+        assert_eq!(player.position(), 1);
+        player.seek("22:00:00").unwrap();
+        assert_eq!(player.position(), 0);
+        player.seek("23:00").unwrap();
+        assert_eq!(player.position(), 1);
+        player.seek("05:59").unwrap();
+        assert_eq!(player.position(), 2);
+        player.seek("2026-01-10 23:30").unwrap();
+        assert_eq!(player.position(), 1);
+        assert!(player.seek("07:00").is_err());
+        assert!(player.seek("later").is_err());
+        assert_eq!(player.position(), 1);
+    }
+}
@@ -0,0 +1,184 @@
+use std::io;
+use std::mem;
+
+/// Nice values `setpriority(2)` accepts.
+pub const NICE_MIN: i32 = -20;
+pub const NICE_MAX: i32 = 19;
+
+/// Priority range of the real-time policies.
+pub const RT_PRIORITY_MAX: u32 = 99;
+
+/// Levels within the real-time and best-effort I/O classes, 0 being the
+/// highest.
+pub const IO_LEVEL_MAX: u32 = 7;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Policy {
+    Other,
+    Fifo,
+    RoundRobin,
+    Batch,
+    Idle,
+    Deadline,
+}
+
+/// The policies `sched_setscheduler(2)` can switch to; SCHED_DEADLINE
+/// needs `sched_setattr(2)` and its runtime parameters.
+pub const POLICIES: [Policy; 5] = [Policy::Other, Policy::Batch, Policy::Idle, Policy::Fifo, Policy::RoundRobin];
+
+impl Policy {
+    /// From the `policy` field of /proc/PID/stat.
+    pub fn from_raw(policy: u32) -> Option<Policy> {
+        match policy as i32 {
+            libc::SCHED_OTHER => Some(Policy::Other),
+            libc::SCHED_FIFO => Some(Policy::Fifo),
+            libc::SCHED_RR => Some(Policy::RoundRobin),
+            libc::SCHED_BATCH => Some(Policy::Batch),
+            libc::SCHED_IDLE => Some(Policy::Idle),
+            libc::SCHED_DEADLINE => Some(Policy::Deadline),
+            _ => None,
+        }
+    }
+
+    fn raw(self) -> i32 {
+        match self {
+            Policy::Other => libc::SCHED_OTHER,
+            Policy::Fifo => libc::SCHED_FIFO,
+            Policy::RoundRobin => libc::SCHED_RR,
+            Policy::Batch => libc::SCHED_BATCH,
+            Policy::Idle => libc::SCHED_IDLE,
+            Policy::Deadline => libc::SCHED_DEADLINE,
+        }
+    }
+
+    /// Short name as `ps -o policy` prints it.
+    pub fn name(self) -> &'static str {
+        match self {
+            Policy::Other => "TS",
+            Policy::Fifo => "FF",
+            Policy::RoundRobin => "RR",
+            Policy::Batch => "B",
+            Policy::Idle => "IDL",
+            Policy::Deadline => "DLN",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Policy::Other => "SCHED_OTHER (normal time sharing)",
+            Policy::Fifo => "SCHED_FIFO (real-time, first in first out)",
+            Policy::RoundRobin => "SCHED_RR (real-time, round robin)",
+            Policy::Batch => "SCHED_BATCH (CPU-bound batch work)",
+            Policy::Idle => "SCHED_IDLE (only when nothing else runs)",
+            Policy::Deadline => "SCHED_DEADLINE",
+        }
+    }
+
+    pub fn is_realtime(self) -> bool {
+        matches!(self, Policy::Fifo | Policy::RoundRobin)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IoClass {
+    /// No class set: best effort, with the level derived from the nice value.
+    None,
+    RealTime,
+    BestEffort,
+    Idle,
+}
+
+pub const IO_CLASSES: [IoClass; 4] = [IoClass::None, IoClass::RealTime, IoClass::BestEffort, IoClass::Idle];
+
+impl IoClass {
+    pub fn name(self) -> &'static str {
+        match self {
+            IoClass::None => "none",
+            IoClass::RealTime => "realtime",
+            IoClass::BestEffort => "best-effort",
+            IoClass::Idle => "idle",
+        }
+    }
+
+    /// Whether the class takes a level.
+    pub fn has_level(self) -> bool {
+        matches!(self, IoClass::RealTime | IoClass::BestEffort)
+    }
+}
+
+// From include/uapi/linux/ioprio.h
+const IOPRIO_CLASS_SHIFT: u32 = 13;
+const IOPRIO_WHO_PROCESS: i32 = 1;
+
+fn check(result: i64) -> io::Result<()> {
+    if result == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// `setpriority(2)` on the process. Lowering the value needs CAP_SYS_NICE.
+pub fn set_nice(pid: i32, nice: i32) -> io::Result<()> {
+    check(unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice.clamp(NICE_MIN, NICE_MAX)) } as i64)
+}
+
+/// `sched_setscheduler(2)`. `rt_priority` is only used by the real-time
+/// policies; the others must be given 0.
+pub fn set_policy(pid: i32, policy: Policy, rt_priority: u32) -> io::Result<()> {
+    let param = libc::sched_param {
+        sched_priority: if policy.is_realtime() { rt_priority.clamp(1, RT_PRIORITY_MAX) as i32 } else { 0 },
+    };
+    check(unsafe { libc::sched_setscheduler(pid, policy.raw(), &param) } as i64)
+}
+
+/// The process's I/O class and level, from `ioprio_get(2)`.
+pub fn io_priority(pid: i32) -> io::Result<(IoClass, u32)> {
+    let value = unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, pid) };
+    if value == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    let class = match value as u32 >> IOPRIO_CLASS_SHIFT {
+        1 => IoClass::RealTime,
+        2 => IoClass::BestEffort,
+        3 => IoClass::Idle,
+        _ => IoClass::None,
+    };
+    Ok((class, value as u32 & ((1 << IOPRIO_CLASS_SHIFT) - 1)))
+}
+
+/// `ioprio_set(2)`. The real-time class needs CAP_SYS_ADMIN.
+pub fn set_io_priority(pid: i32, class: IoClass, level: u32) -> io::Result<()> {
+    let class_bits: u32 = match class {
+        IoClass::None => 0,
+        IoClass::RealTime => 1,
+        IoClass::BestEffort => 2,
+        IoClass::Idle => 3,
+    };
+    let level = if class.has_level() { level.min(IO_LEVEL_MAX) } else { 0 };
+    let value = (class_bits << IOPRIO_CLASS_SHIFT) | level;
+    check(unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, pid, value) })
+}
+
+/// Number of CPUs configured, whether online or not; affinity masks are
+/// indexed by these.
+pub fn cpu_count() -> usize {
+    (unsafe { libc::sysconf(libc::_SC_NPROCESSORS_CONF) }).max(1) as usize
+}
+
+/// The CPUs the process may run on, from `sched_getaffinity(2)`, as one
+/// flag per CPU.
+pub fn affinity(pid: i32) -> io::Result<Vec<bool>> {
+    let mut set: libc::cpu_set_t = unsafe { mem::zeroed() };
+    check(unsafe { libc::sched_getaffinity(pid, mem::size_of::<libc::cpu_set_t>(), &mut set) } as i64)?;
+    Ok((0..cpu_count()).map(|cpu| unsafe { libc::CPU_ISSET(cpu, &set) }).collect())
+}
+
+/// `sched_setaffinity(2)`: allow the process on the CPUs flagged in `cpus`.
+pub fn set_affinity(pid: i32, cpus: &[bool]) -> io::Result<()> {
+    let mut set: libc::cpu_set_t = unsafe { mem::zeroed() };
+    for (cpu, _) in cpus.iter().enumerate().filter(|(_, &allowed)| allowed) {
+        unsafe { libc::CPU_SET(cpu, &mut set) };
+    }
+    check(unsafe { libc::sched_setaffinity(pid, mem::size_of::<libc::cpu_set_t>(), &set) } as i64)
+}
@@ -0,0 +1,69 @@
+use std::io;
+
+/// The standard signals, in number order.
+const STANDARD_SIGNALS: [(&str, i32); 31] = [
+    ("SIGHUP", libc::SIGHUP),
+    ("SIGINT", libc::SIGINT),
+    ("SIGQUIT", libc::SIGQUIT),
+    ("SIGILL", libc::SIGILL),
+    ("SIGTRAP", libc::SIGTRAP),
+    ("SIGABRT", libc::SIGABRT),
+    ("SIGBUS", libc::SIGBUS),
+    ("SIGFPE", libc::SIGFPE),
+    ("SIGKILL", libc::SIGKILL),
+    ("SIGUSR1", libc::SIGUSR1),
+    ("SIGSEGV", libc::SIGSEGV),
+    ("SIGUSR2", libc::SIGUSR2),
+    ("SIGPIPE", libc::SIGPIPE),
+    ("SIGALRM", libc::SIGALRM),
+    ("SIGTERM", libc::SIGTERM),
+    ("SIGSTKFLT", libc::SIGSTKFLT),
+    ("SIGCHLD", libc::SIGCHLD),
+    ("SIGCONT", libc::SIGCONT),
+    ("SIGSTOP", libc::SIGSTOP),
+    ("SIGTSTP", libc::SIGTSTP),
+    ("SIGTTIN", libc::SIGTTIN),
+    ("SIGTTOU", libc::SIGTTOU),
+    ("SIGURG", libc::SIGURG),
+    ("SIGXCPU", libc::SIGXCPU),
+    ("SIGXFSZ", libc::SIGXFSZ),
+    ("SIGVTALRM", libc::SIGVTALRM),
+    ("SIGPROF", libc::SIGPROF),
+    ("SIGWINCH", libc::SIGWINCH),
+    ("SIGIO", libc::SIGIO),
+    ("SIGPWR", libc::SIGPWR),
+    ("SIGSYS", libc::SIGSYS),
+];
+
+/// Every signal `kill(1)` can send, as (number, name), including the
+/// real-time ones as `SIGRTMIN+n`.
+pub fn all_signals() -> Vec<(i32, String)> {
+    let mut signals: Vec<(i32, String)> = STANDARD_SIGNALS
+        .iter()
+        .map(|&(name, number)| (number, name.to_string()))
+        .collect();
+    signals.sort_by_key(|&(number, _)| number);
+    signals.extend((libc::SIGRTMIN()..=libc::SIGRTMAX()).map(|number| (number, signal_name(number))));
+    signals
+}
+
+/// `SIGTERM`, `SIGRTMIN+3`, or the bare number for anything unknown.
+pub fn signal_name(signal: i32) -> String {
+    if let Some((name, _)) = STANDARD_SIGNALS.iter().find(|&&(_, number)| number == signal) {
+        return name.to_string();
+    }
+    match signal - libc::SIGRTMIN() {
+        0 => "SIGRTMIN".to_string(),
+        offset if offset > 0 && signal <= libc::SIGRTMAX() => format!("SIGRTMIN+{}", offset),
+        _ => signal.to_string(),
+    }
+}
+
+/// Sends `signal` to `pid`, with the OS error (ESRCH, EPERM) on failure.
+pub fn send(pid: i32, signal: i32) -> io::Result<()> {
+    if unsafe { libc::kill(pid, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
@@ -0,0 +1,298 @@
+use crate::source::DataSource;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Protocol {
+    #[default]
+    Tcp,
+    Tcp6,
+    Udp,
+    Udp6,
+    Unix,
+}
+
+impl Protocol {
+    pub fn name(self) -> &'static str {
+        match self {
+            Protocol::Tcp => "tcp",
+            Protocol::Tcp6 => "tcp6",
+            Protocol::Udp => "udp",
+            Protocol::Udp6 => "udp6",
+            Protocol::Unix => "unix",
+        }
+    }
+
+    pub fn is_tcp(self) -> bool {
+        matches!(self, Protocol::Tcp | Protocol::Tcp6)
+    }
+}
+
+/// One socket, as `ss -anp` would show it.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SocketInfo {
+    pub protocol: Protocol,
+    /// State in `ss` spelling: `LISTEN`, `ESTAB`, `TIME-WAIT`, `UNCONN`, ...
+    pub state: String,
+    /// `addr:port` for IP sockets, the path (or `@name`, `*`) for unix ones.
+    pub local: String,
+    pub remote: String,
+    pub inode: u64,
+    pub uid: u32,
+    /// Processes holding the socket open, as (PID, command name).
+    pub processes: Vec<(i32, String)>,
+}
+
+impl SocketInfo {
+    /// Listening TCP and unix sockets, and unconnected UDP ones, like `ss -l`.
+    pub fn is_listening(&self) -> bool {
+        self.state == "LISTEN" || (matches!(self.protocol, Protocol::Udp | Protocol::Udp6) && self.state == "UNCONN")
+    }
+}
+
+/// Number of TCP sockets (v4 and v6) in each state.
+pub fn tcp_state_counts(sockets: &[SocketInfo]) -> BTreeMap<&str, usize> {
+    let mut counts = BTreeMap::new();
+    for socket in sockets.iter().filter(|s| s.protocol.is_tcp()) {
+        *counts.entry(socket.state.as_str()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Reads /proc/net/{tcp,tcp6,udp,udp6,unix} and finds the owner of each
+/// socket by scanning /proc/PID/fd for `socket:[inode]` links. Sockets of
+/// processes we can't inspect are listed without an owner.
+pub fn collect_sockets(source: &DataSource) -> Vec<SocketInfo> {
+    let mut sockets = Vec::new();
+    for (protocol, file) in [
+        (Protocol::Tcp, "net/tcp"),
+        (Protocol::Tcp6, "net/tcp6"),
+        (Protocol::Udp, "net/udp"),
+        (Protocol::Udp6, "net/udp6"),
+    ] {
+        if let Ok(f) = source.open_proc(file) {
+            sockets.extend(
+                BufReader::new(f)
+                    .lines()
+                    .map_while(Result::ok)
+                    .skip(1)
+                    .filter_map(|line| parse_inet_line(protocol, &line)),
+            );
+        }
+    }
+    if let Ok(f) = source.open_proc("net/unix") {
+        sockets.extend(
+            BufReader::new(f)
+                .lines()
+                .map_while(Result::ok)
+                .skip(1)
+                .filter_map(|line| parse_unix_line(&line)),
+        );
+    }
+
+    let owners = socket_owners(source);
+    for socket in sockets.iter_mut() {
+        if let Some(processes) = owners.get(&socket.inode) {
+            socket.processes = processes.clone();
+        }
+    }
+    sockets
+}
+
+fn parse_inet_line(protocol: Protocol, line: &str) -> Option<SocketInfo> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 10 {
+        return None;
+    }
+    let state = u8::from_str_radix(fields[3], 16).ok()?;
+    Some(SocketInfo {
+        protocol,
+        state: tcp_state_name(state).to_string(),
+        local: parse_address(fields[1])?,
+        remote: parse_address(fields[2])?,
+        inode: fields[9].parse().ok()?,
+        uid: fields[7].parse().ok()?,
+        processes: Vec::new(),
+    })
+}
+
+/// The kernel's TCP state numbers (include/net/tcp_states.h). UDP reuses
+/// ESTABLISHED for connected sockets and CLOSE for the rest.
+fn tcp_state_name(state: u8) -> &'static str {
+    match state {
+        0x01 => "ESTAB",
+        0x02 => "SYN-SENT",
+        0x03 => "SYN-RECV",
+        0x04 => "FIN-WAIT-1",
+        0x05 => "FIN-WAIT-2",
+        0x06 => "TIME-WAIT",
+        0x07 => "UNCONN",
+        0x08 => "CLOSE-WAIT",
+        0x09 => "LAST-ACK",
+        0x0A => "LISTEN",
+        0x0B => "CLOSING",
+        0x0C => "NEW-SYN-RECV",
+        _ => "UNKNOWN",
+    }
+}
+
+/// `0100007F:0035` is 127.0.0.1:53. The address is in network byte order
+/// but printed as 32-bit words in host (little-endian) order.
+fn parse_address(s: &str) -> Option<String> {
+    let (addr, port) = s.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let mut bytes = Vec::with_capacity(16);
+    for word in addr.as_bytes().chunks(8) {
+        let word = u32::from_str_radix(std::str::from_utf8(word).ok()?, 16).ok()?;
+        bytes.extend_from_slice(&word.to_le_bytes());
+    }
+    let port = if port == 0 { "*".to_string() } else { port.to_string() };
+    match bytes.len() {
+        4 => {
+            let ip = Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]);
+            let ip = if ip.is_unspecified() { "*".to_string() } else { ip.to_string() };
+            Some(format!("{}:{}", ip, port))
+        }
+        16 => {
+            let ip = Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?);
+            let ip = if ip.is_unspecified() { "*".to_string() } else { format!("[{}]", ip) };
+            Some(format!("{}:{}", ip, port))
+        }
+        _ => None,
+    }
+}
+
+/// `Num RefCount Protocol Flags Type St Inode [Path]`. The path is the rest
+/// of the line, spaces included.
+fn parse_unix_line(line: &str) -> Option<SocketInfo> {
+    let mut fields = Vec::with_capacity(7);
+    let mut rest = line.trim_start();
+    while fields.len() < 7 {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        if end == 0 {
+            return None;
+        }
+        fields.push(&rest[..end]);
+        rest = &rest[end..];
+        if fields.len() < 7 {
+            rest = rest.trim_start();
+        }
+    }
+    // One space separates the inode from the path
+    let path = rest.strip_prefix(' ').filter(|path| !path.is_empty()).unwrap_or("*");
+    let flags = u32::from_str_radix(fields[3], 16).ok()?;
+    // __SO_ACCEPTCON marks a listening socket
+    let state = if flags & 0x0001_0000 != 0 {
+        "LISTEN"
+    } else {
+        match fields[5] {
+            "02" => "SYN-SENT",
+            "03" => "ESTAB",
+            _ => "UNCONN",
+        }
+    };
+    Some(SocketInfo {
+        protocol: Protocol::Unix,
+        state: state.to_string(),
+        local: path.to_string(),
+        remote: "*".to_string(),
+        inode: fields[6].parse().ok()?,
+        uid: 0,
+        processes: Vec::new(),
+    })
+}
+
+/// socket inode -> processes with it open.
+fn socket_owners(source: &DataSource) -> HashMap<u64, Vec<(i32, String)>> {
+    let mut owners: HashMap<u64, Vec<(i32, String)>> = HashMap::new();
+    for entry in fs::read_dir(source.proc_root()).into_iter().flatten().flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<i32>().ok()) else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        let mut command = None;
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            let inode = target
+                .to_str()
+                .and_then(|t| t.strip_prefix("socket:["))
+                .and_then(|t| t.strip_suffix(']'))
+                .and_then(|t| t.parse().ok());
+            if let Some(inode) = inode {
+                let command = command.get_or_insert_with(|| {
+                    fs::read_to_string(entry.path().join("comm"))
+                        .map(|comm| comm.trim_end().to_string())
+                        .unwrap_or_default()
+                });
+                let processes = owners.entry(inode).or_default();
+                // A process with the socket on several fds is listed once
+                if !processes.iter().any(|(p, _)| *p == pid) {
+                    processes.push((pid, command.clone()));
+                }
+            }
+        }
+    }
+    owners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ipv4_address() {
+        assert_eq!(parse_address("0100007F:0035").as_deref(), Some("127.0.0.1:53"));
+        assert_eq!(parse_address("00000000:0000").as_deref(), Some("*:*"));
+        assert_eq!(parse_address("0100007F"), None);
+        assert_eq!(parse_address("0100007G:0035"), None);
+    }
+
+    #[test]
+    fn parse_ipv6_address() {
+        assert_eq!(
+            parse_address("00000000000000000000000001000000:0277").as_deref(),
+            Some("[::1]:631")
+        );
+        assert_eq!(
+            parse_address("0000000000000000FFFF00000100007F:01BB").as_deref(),
+            Some("[::ffff:127.0.0.1]:443")
+        );
+    }
+
+    #[test]
+    fn parse_unix_lines() {
+        let socket = parse_unix_line("0000000000000000: 00000002 00000000 00010000 0001 01 23456 /run/my app/ctl sock").unwrap();
+        assert_eq!(socket.state, "LISTEN");
+        assert_eq!(socket.local, "/run/my app/ctl sock");
+        assert_eq!(socket.inode, 23456);
+
+        let socket = parse_unix_line("0000000000000000: 00000003 00000000 00000000 0001 03 23457 @dbus vm").unwrap();
+        assert_eq!(socket.state, "ESTAB");
+        assert_eq!(socket.local, "@dbus vm");
+
+        let socket = parse_unix_line("0000000000000000: 00000002 00000000 00000000 0002 01 23458").unwrap();
+        assert_eq!(socket.state, "UNCONN");
+        assert_eq!(socket.local, "*");
+
+        assert!(parse_unix_line("0000000000000000: 00000002 00000000").is_none());
+    }
+
+    #[test]
+    fn parse_tcp6_line() {
+        let line = "   1: 000080FE00000000FF005450B6AD1DFE:0016 000080FE00000000FF005450A1B2C3D4:D431 01 \
+                    00000000:00000000 02:000A7214 00000000     0        0 48210 2 0000000000000000 20 4 30 10 -1";
+        let socket = parse_inet_line(Protocol::Tcp6, line).unwrap();
+        assert_eq!(socket.state, "ESTAB");
+        assert_eq!(socket.local, "[fe80::5054:ff:fe1d:adb6]:22");
+        assert_eq!(socket.remote, "[fe80::5054:ff:d4c3:b2a1]:54321");
+        assert_eq!(socket.inode, 48210);
+        assert_eq!(socket.uid, 0);
+    }
+}
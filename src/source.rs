@@ -0,0 +1,99 @@
+use users::get_user_by_uid;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Where the collectors read kernel data from.
+///
+/// The live system is read through `/proc` and `/sys`. A source rooted
+/// elsewhere reads a captured tree instead (e.g. `capture/proc` and
+/// `capture/sys`), which makes runs against fixtures or another machine's
+/// data reproducible. Such a tree may also hold an `etc/passwd` for user
+/// names and a `dmesg` text dump for crash tracking.
+#[derive(Clone, Debug)]
+pub struct DataSource {
+    proc_root: PathBuf,
+    sys_root: PathBuf,
+    // uid -> name from the captured passwd; `None` means use the host's
+    users: Option<HashMap<u32, String>>,
+    dmesg: Option<PathBuf>,
+}
+
+impl Default for DataSource {
+    fn default() -> DataSource {
+        DataSource::live()
+    }
+}
+
+impl DataSource {
+    /// The running system.
+    pub fn live() -> DataSource {
+        DataSource {
+            proc_root: PathBuf::from("/proc"),
+            sys_root: PathBuf::from("/sys"),
+            users: None,
+            dmesg: None,
+        }
+    }
+
+    /// A captured tree with `proc/` and `sys/` directories under `root`.
+    pub fn with_root(root: impl AsRef<Path>) -> DataSource {
+        let root = root.as_ref();
+        DataSource {
+            proc_root: root.join("proc"),
+            sys_root: root.join("sys"),
+            users: fs::read_to_string(root.join("etc/passwd"))
+                .ok()
+                .map(|passwd| parse_passwd(&passwd)),
+            dmesg: Some(root.join("dmesg")),
+        }
+    }
+
+    pub fn is_live(&self) -> bool {
+        self.dmesg.is_none()
+    }
+
+    pub fn proc_root(&self) -> &Path {
+        &self.proc_root
+    }
+
+    /// Path of `relative` under the procfs root, e.g. `proc_path("net/dev")`.
+    pub fn proc_path(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.proc_root.join(relative)
+    }
+
+    /// Path of `relative` under the sysfs root, e.g. `sys_path("class/net")`.
+    pub fn sys_path(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.sys_root.join(relative)
+    }
+
+    pub fn open_proc(&self, relative: impl AsRef<Path>) -> io::Result<File> {
+        File::open(self.proc_path(relative))
+    }
+
+    pub fn user_name(&self, uid: u32) -> String {
+        match &self.users {
+            Some(users) => users.get(&uid).cloned(),
+            None => get_user_by_uid(uid).map(|user| user.name().to_string_lossy().to_string()),
+        }
+        .unwrap_or_else(|| "N/A".to_string())
+    }
+
+    /// Captured kernel log of a non-live source, if there is one.
+    pub fn dmesg_path(&self) -> Option<&Path> {
+        self.dmesg.as_deref()
+    }
+}
+
+fn parse_passwd(passwd: &str) -> HashMap<u32, String> {
+    passwd
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let uid = fields.nth(1)?.parse().ok()?;
+            Some((uid, name.to_string()))
+        })
+        .collect()
+}
@@ -0,0 +1,90 @@
+use crate::cpu::{CpuSampler, CpuStats};
+use crate::disk::{DiskSampler, DiskStats};
+use crate::memory::MemoryStats;
+use crate::network::{InterfaceStats, NetworkSampler};
+use crate::source::DataSource;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead};
+
+/// Load averages over the last 1, 5 and 15 minutes.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LoadAverage {
+    pub one: f32,
+    pub five: f32,
+    pub fifteen: f32,
+}
+
+/// System-wide figures shown in the header of the monitor.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SystemStats {
+    pub load_avg: LoadAverage,
+    pub memory: MemoryStats,
+    pub network: Vec<InterfaceStats>,
+    pub cpu_speeds: Vec<f64>,
+    pub cpu: CpuStats,
+    pub disks: Vec<DiskStats>,
+}
+
+/// Keeps the previous samples needed to turn cumulative kernel counters
+/// into rates.
+#[derive(Default)]
+pub struct SystemSampler {
+    cpu: CpuSampler,
+    network: NetworkSampler,
+    disk: DiskSampler,
+    /// Leave loopback and virtual interfaces out of the network stats.
+    pub exclude_virtual_interfaces: bool,
+}
+
+impl SystemSampler {
+    pub fn collect(&mut self, source: &DataSource) -> crate::Result<SystemStats> {
+        let load_avg = procfs::LoadAverage::from_reader(source.open_proc("loadavg")?)?;
+
+        Ok(SystemStats {
+            load_avg: LoadAverage {
+                one: load_avg.one,
+                five: load_avg.five,
+                fifteen: load_avg.fifteen,
+            },
+            memory: MemoryStats::collect(source)?,
+            network: self.network.sample(source, self.exclude_virtual_interfaces)?,
+            cpu_speeds: get_cpu_speeds(source)?,
+            cpu: self.cpu.sample(source)?,
+            disks: self.disk.sample(source)?,
+        })
+    }
+}
+
+impl SystemStats {
+    /// Received and transmitted bytes per second over all listed interfaces.
+    pub fn network_throughput(&self) -> (f64, f64) {
+        self.network.iter().fold((0.0, 0.0), |(rx, tx), iface| {
+            (rx + iface.rx_bytes_per_sec, tx + iface.tx_bytes_per_sec)
+        })
+    }
+
+    /// Read and written bytes per second over all whole disks.
+    pub fn disk_throughput(&self) -> (f64, f64) {
+        self.disks.iter().fold((0.0, 0.0), |(read, write), disk| {
+            (read + disk.read_bytes_per_sec, write + disk.write_bytes_per_sec)
+        })
+    }
+}
+
+// CPU speeds
+pub fn get_cpu_speeds(source: &DataSource) -> io::Result<Vec<f64>> {
+    let file = source.open_proc("cpuinfo")?;
+    let reader = io::BufReader::new(file);
+
+    let mut speeds = vec![];
+
+    for line in reader.lines().map_while(Result::ok) {
+        if line.starts_with("cpu MHz") {
+            if let Some(value) = line.split(':').nth(1) {
+                speeds.push(value.trim().parse::<f64>().unwrap_or(0.0));
+            }
+        }
+    }
+
+    Ok(speeds)
+}
@@ -0,0 +1,1335 @@
+use super::columns::{Column, ALL_COLUMNS, DEFAULT_COLUMNS};
+use super::ui::process_detail_lines;
+use crate::cli::Args;
+use crossterm::event::KeyCode;
+use libc::{SIGCONT, SIGKILL, SIGSTOP, SIGTERM};
+use os_project::process::{is_running, sort_processes, subtree_bottom_up};
+use os_project::sched::{self, Policy, IO_CLASSES, IO_LEVEL_MAX, NICE_MAX, NICE_MIN, POLICIES, RT_PRIORITY_MAX};
+use os_project::files::{find_open_file, FileUser};
+use os_project::filter::matches_text;
+use os_project::signal::{self, all_signals, signal_name};
+use os_project::sockets::{Protocol, SocketInfo};
+use os_project::{
+    CollectorOptions, CpuMode, DataSource, Filter, Process, ProcessSelection, Snapshot, SortCriteria, ThreadInfo,
+};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Rows of the process list and tree on screen at once.
+pub const PAGE_ROWS: usize = 20;
+
+/// Characters moved per `<`/`>` press.
+const TEXT_SCROLL_STEP: usize = 8;
+
+/// How long a subtree shutdown waits for SIGKILL to take effect before
+/// reporting what survived it.
+const KILL_WAIT: Duration = Duration::from_secs(2);
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ViewState {
+    Processes,
+    CrashTracking,
+    ProcessTree,
+    Network,
+    Sockets,
+    Disks,
+    Memory,
+    /// Full details of one process, opened with Enter; not in the ←/→ cycle.
+    Detail,
+    /// Open file descriptors of the detail process (`o`).
+    Files,
+    /// Results of a "who has this file open" search (`O`).
+    FileSearch,
+}
+
+const VIEW_STATES: [ViewState; 7] = [
+    ViewState::Processes,
+    ViewState::CrashTracking,
+    ViewState::ProcessTree,
+    ViewState::Network,
+    ViewState::Sockets,
+    ViewState::Disks,
+    ViewState::Memory,
+];
+
+impl FromStr for ViewState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ViewState, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "processes" | "list" => Ok(ViewState::Processes),
+            "crashes" | "crash" => Ok(ViewState::CrashTracking),
+            "tree" => Ok(ViewState::ProcessTree),
+            "network" | "net" => Ok(ViewState::Network),
+            "sockets" | "ss" => Ok(ViewState::Sockets),
+            "disks" | "disk" => Ok(ViewState::Disks),
+            "memory" | "mem" => Ok(ViewState::Memory),
+            _ => Err(format!(
+                "unknown view '{}' (expected processes, crashes, tree, network, sockets, disks or memory)",
+                s
+            )),
+        }
+    }
+}
+
+/// One line of the process tree.
+pub struct TreeRow {
+    /// Index into the snapshot's process table.
+    pub index: usize,
+    /// `│  ├─ ` style guides leading up to the node.
+    pub prefix: String,
+    pub has_children: bool,
+    pub collapsed: bool,
+}
+
+/// What the main loop should do after a key press.
+#[derive(PartialEq, Eq, Debug)]
+pub enum Action {
+    None,
+    Quit,
+    TogglePause,
+    StepForward,
+    StepBack,
+    Seek(String),
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PromptKind {
+    Seek,
+    Search,
+    Filter,
+    OpenFile,
+}
+
+/// A line of text being typed in the help bar.
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: String,
+}
+
+/// A process a control is about to act on, as it was when the dialog was
+/// opened, so the confirmation names what the user picked even if the list
+/// has moved underneath.
+#[derive(Clone)]
+pub struct SignalTarget {
+    pub pid: i32,
+    pub command: String,
+    pub user: String,
+}
+
+/// A popup that takes all keys until it is closed.
+pub enum Dialog {
+    /// Picking a signal; `cursor` indexes [`all_signals`].
+    Signal { targets: Vec<SignalTarget>, cursor: usize },
+    /// Last chance to back out before the signal is sent.
+    ConfirmSignal { targets: Vec<SignalTarget>, signal: i32 },
+    /// Last chance to back out of shutting down `pids`, the target's
+    /// subtree, children first.
+    ConfirmShutdown { target: SignalTarget, pids: Vec<i32> },
+    Shutdown(Shutdown),
+    /// Picking a scheduling policy; `cursor` indexes [`POLICIES`].
+    Scheduler { targets: Vec<SignalTarget>, cursor: usize, rt_priority: u32 },
+    /// Picking an I/O class and level; `cursor` indexes [`IO_CLASSES`].
+    IoPriority { targets: Vec<SignalTarget>, cursor: usize, level: u32 },
+    /// One checkbox per CPU.
+    Affinity { targets: Vec<SignalTarget>, cpus: Vec<bool>, cursor: usize },
+}
+
+/// A subtree shutdown in progress: SIGTERM has gone to every process, and
+/// whatever is still running at `deadline` gets SIGKILL.
+pub struct Shutdown {
+    pub target: SignalTarget,
+    /// Processes that were sent SIGTERM, children first.
+    pub pids: Vec<i32>,
+    /// Those of `pids` still running at the last check.
+    pub alive: Vec<i32>,
+    /// Processes we were not allowed to signal.
+    pub denied: Vec<i32>,
+    pub deadline: Instant,
+    /// SIGKILL has been sent and `deadline` is now the end of [`KILL_WAIT`].
+    pub escalated: bool,
+}
+
+/// Where a replay is, for the help bar.
+#[derive(Clone, Copy)]
+pub struct ReplayStatus {
+    pub position: usize,
+    pub len: usize,
+    pub paused: bool,
+}
+
+/// State of the terminal front end, fed by snapshots from the collector.
+pub struct App {
+    pub snapshot: Snapshot,
+    pub scroll_offset: usize,
+    pub selected_index: usize,
+    pub sort_criteria: SortCriteria,
+    pub view_state: ViewState,
+    /// Highlighted node of the process tree.
+    pub tree_view_pid: Option<i32>,
+    /// First row of the process tree on screen.
+    pub tree_scroll: usize,
+    /// Tree nodes whose children are hidden.
+    pub collapsed: HashSet<i32>,
+    /// First /proc/meminfo line shown in the memory panel.
+    pub memory_scroll: usize,
+    /// First line shown in the process detail pane.
+    pub detail_scroll: usize,
+    /// First row shown in the socket list.
+    pub sockets_scroll: usize,
+    /// Only show listening sockets.
+    pub listening_only: bool,
+    pub show_unix_sockets: bool,
+    /// First row shown in the open files and file search views.
+    pub files_scroll: usize,
+    /// Last "who has this file open" search and what it found.
+    pub file_query: String,
+    pub file_users: Vec<FileUser>,
+    pub options: CollectorOptions,
+    /// Visible process table columns, in display order.
+    pub columns: Vec<Column>,
+    /// Characters hidden off the left of the command and exe columns.
+    pub text_scroll: usize,
+    /// Last search, for `n`/`N`.
+    pub search: Option<String>,
+    /// Rows not matching this are hidden from the process table.
+    pub filter: Option<Filter>,
+    /// Why the filter being typed doesn't parse.
+    pub filter_error: Option<String>,
+    /// List each process's threads under it (`H` in the list).
+    pub show_threads: bool,
+    /// Show the thread table in the detail pane (`H` there).
+    pub detail_threads: bool,
+    /// Highlighted row of the field picker while it is open.
+    pub field_picker: Option<usize>,
+    pub dialog: Option<Dialog>,
+    /// PIDs the process controls act on instead of the selected row.
+    pub tagged: HashSet<i32>,
+    /// Time between SIGTERM and SIGKILL in a subtree shutdown.
+    pub kill_grace: Duration,
+    pub prompt: Option<Prompt>,
+    /// One-line message shown in the help bar until the next key press.
+    pub status: Option<String>,
+    /// Set when showing a recording instead of the live system.
+    pub replay: Option<ReplayStatus>,
+    selection: ProcessSelection,
+    // For one-off reads such as the open file search
+    source: DataSource,
+    // Rows passing the filter, as an index into snapshot.processes and,
+    // for thread rows, into that process's tasks
+    visible: Vec<(usize, Option<usize>)>,
+    view_i: usize,
+    // View to go back to when the memory panel is collapsed
+    previous_view: ViewState,
+    // List or tree the detail views were opened from
+    detail_from: ViewState,
+    // Where Esc leaves the open files view for: the detail pane, or the
+    // list or tree it was opened from
+    files_from: ViewState,
+}
+
+impl App {
+    pub fn new(args: &Args, snapshot: Snapshot) -> App {
+        let mut app = App {
+            snapshot,
+            scroll_offset: 0,
+            selected_index: 0,
+            sort_criteria: args.sort,
+            view_state: ViewState::Processes,
+            tree_view_pid: None,
+            tree_scroll: 0,
+            collapsed: HashSet::new(),
+            memory_scroll: 0,
+            detail_scroll: 0,
+            sockets_scroll: 0,
+            listening_only: false,
+            show_unix_sockets: true,
+            files_scroll: 0,
+            file_query: String::new(),
+            file_users: Vec::new(),
+            options: args.options(),
+            columns: DEFAULT_COLUMNS.to_vec(),
+            text_scroll: 0,
+            search: None,
+            filter: args.filter.clone(),
+            filter_error: None,
+            show_threads: false,
+            detail_threads: false,
+            field_picker: None,
+            dialog: None,
+            tagged: HashSet::new(),
+            kill_grace: args.kill_grace(),
+            prompt: None,
+            status: None,
+            replay: None,
+            selection: args.selection(),
+            source: args.source(),
+            visible: Vec::new(),
+            view_i: 0,
+            previous_view: ViewState::Processes,
+            detail_from: ViewState::Processes,
+            files_from: ViewState::Processes,
+        };
+        app.set_view(args.view);
+        app.refresh();
+        app
+    }
+
+    pub fn set_snapshot(&mut self, snapshot: Snapshot) {
+        self.snapshot = snapshot;
+        self.refresh();
+    }
+
+    fn refresh(&mut self) {
+        let selection = &self.selection;
+        self.snapshot.processes.retain(|p| selection.matches(p));
+        // Tags of processes that have exited would otherwise catch a reused PID
+        let pids: HashSet<i32> = self.snapshot.processes.iter().map(|p| p.pid).collect();
+        self.tagged.retain(|pid| pids.contains(pid));
+        self.sort();
+    }
+
+    /// The rows of the process table, in display order; thread rows come
+    /// with the thread.
+    pub fn rows(&self) -> impl Iterator<Item = (&Process, Option<&ThreadInfo>)> + '_ {
+        self.visible.iter().map(|&(i, task)| {
+            let process = &self.snapshot.processes[i];
+            (process, task.map(|t| &process.tasks[t]))
+        })
+    }
+
+    fn sort(&mut self) {
+        sort_processes(&mut self.snapshot.processes, self.sort_criteria);
+        self.apply_filter();
+    }
+
+    fn apply_filter(&mut self) {
+        let filter = self.filter.as_ref();
+        self.visible.clear();
+        for (i, p) in self.snapshot.processes.iter().enumerate() {
+            if filter.is_some_and(|f| !f.matches(p)) {
+                continue;
+            }
+            self.visible.push((i, None));
+            if self.show_threads {
+                // The main thread is the process row itself
+                let threads = p.tasks.iter().enumerate().filter(|(_, t)| t.tid != p.pid);
+                self.visible.extend(threads.map(|(t, _)| (i, Some(t))));
+            }
+        }
+        self.selected_index = self.selected_index.min(self.visible.len().saturating_sub(1));
+        self.scroll_to_selection();
+    }
+
+    /// Sockets passing the listening and unix toggles, in display order.
+    pub fn sockets(&self) -> impl Iterator<Item = &SocketInfo> + '_ {
+        self.snapshot.sockets.iter().filter(|s| {
+            (!self.listening_only || s.is_listening()) && (self.show_unix_sockets || s.protocol != Protocol::Unix)
+        })
+    }
+
+    /// The highlighted process of the list, or of the tree while it is shown.
+    fn selected_process(&self) -> Option<&Process> {
+        if self.view_state == ViewState::ProcessTree {
+            let rows = self.tree_rows();
+            let index = rows.get(self.tree_position(&rows))?.index;
+            return self.snapshot.processes.get(index);
+        }
+        self.visible.get(self.selected_index).map(|&(i, _)| &self.snapshot.processes[i])
+    }
+
+    /// Handles one key press and tells the main loop what to do next.
+    pub fn handle_key(&mut self, code: KeyCode) -> Action {
+        self.status = None;
+        if self.prompt.is_some() {
+            return self.handle_prompt_key(code);
+        }
+        if self.field_picker.is_some() {
+            self.handle_field_picker_key(code);
+            return Action::None;
+        }
+        if self.dialog.is_some() {
+            self.handle_dialog_key(code);
+            return Action::None;
+        }
+
+        match code {
+            KeyCode::Char('q') => return Action::Quit,
+            KeyCode::Char('k') if self.in_process_view() => self.open_signal_menu(),
+            KeyCode::Char('K') if self.in_process_view() => self.confirm_shutdown(),
+            KeyCode::Char(']') if self.in_process_view() => self.renice_targets(-1),
+            KeyCode::Char('[') if self.in_process_view() => self.renice_targets(1),
+            KeyCode::Char(' ') if self.in_process_view() => {
+                self.toggle_tag();
+                // Move on, so a run of rows can be tagged by holding space
+                if self.view_state == ViewState::ProcessTree {
+                    self.move_in_tree(1);
+                } else {
+                    self.select_next();
+                }
+            }
+            KeyCode::Char('T') if self.in_process_view() => self.tag_subtree(),
+            KeyCode::Char('F') if self.in_process_view() => self.tag_filtered(),
+            KeyCode::Char('U') if self.in_process_view() => self.tagged.clear(),
+            KeyCode::Char('S') if self.in_process_view() => self.open_scheduler(),
+            KeyCode::Char('i') if self.in_process_view() => self.open_io_priority(),
+            KeyCode::Char('a') if self.in_process_view() => self.open_affinity(),
+            KeyCode::Char('t') if self.view_state == ViewState::Processes => {
+                if let Some(pid) = self.selected_process().map(|p| p.pid) {
+                    self.show_in_tree(pid);
+                    self.set_view(ViewState::ProcessTree);
+                }
+            }
+            KeyCode::Char('s') if self.in_process_view() => self.signal_targets(SIGSTOP, "suspend", "Suspended"),
+            KeyCode::Char('w') if self.in_process_view() => self.signal_targets(SIGCONT, "resume", "Resumed"),
+            KeyCode::Down if self.view_state == ViewState::ProcessTree => self.move_in_tree(1),
+            KeyCode::Up if self.view_state == ViewState::ProcessTree => self.move_in_tree(-1),
+            KeyCode::PageDown if self.view_state == ViewState::ProcessTree => self.move_in_tree(PAGE_ROWS as isize),
+            KeyCode::PageUp if self.view_state == ViewState::ProcessTree => self.move_in_tree(-(PAGE_ROWS as isize)),
+            // Space tags in the tree as in the list, so folding is on Tab
+            KeyCode::Tab if self.view_state == ViewState::ProcessTree => {
+                if let Some(pid) = self.tree_view_pid {
+                    if !self.collapsed.remove(&pid) {
+                        self.collapsed.insert(pid);
+                    }
+                }
+            }
+            KeyCode::Char('-') if self.view_state == ViewState::ProcessTree => {
+                if let Some(pid) = self.tree_view_pid {
+                    self.collapsed.insert(pid);
+                }
+            }
+            KeyCode::Char('+') if self.view_state == ViewState::ProcessTree => {
+                if let Some(pid) = self.tree_view_pid {
+                    self.collapsed.remove(&pid);
+                }
+            }
+            KeyCode::Char('*') if self.view_state == ViewState::ProcessTree => self.collapsed.clear(),
+            KeyCode::Char('f') if self.view_state == ViewState::Processes => {
+                self.field_picker = Some(0);
+            }
+            KeyCode::Enter if self.in_process_view() => self.open_detail(ViewState::Detail),
+            KeyCode::Char('o') if self.in_process_view() => {
+                self.open_detail(ViewState::Files);
+            }
+            KeyCode::Char('o') if self.view_state == ViewState::Detail => {
+                self.files_scroll = 0;
+                self.files_from = ViewState::Detail;
+                self.set_view(ViewState::Files);
+            }
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Backspace if self.view_state == ViewState::Detail => {
+                self.close_detail();
+            }
+            KeyCode::Esc | KeyCode::Backspace if self.view_state == ViewState::Files => {
+                if self.files_from == ViewState::Detail {
+                    self.set_view(ViewState::Detail);
+                } else {
+                    self.close_detail();
+                }
+            }
+            KeyCode::Esc | KeyCode::Backspace if self.view_state == ViewState::FileSearch => {
+                self.set_view(self.previous_view);
+            }
+            KeyCode::Char('O') => {
+                if self.replay.is_some() {
+                    self.status = Some("Cannot search open files of a recording".to_string());
+                } else {
+                    self.open_prompt(PromptKind::OpenFile);
+                }
+            }
+            KeyCode::Down if self.in_files_view() => self.scroll_files(1),
+            KeyCode::PageDown if self.in_files_view() => self.scroll_files(20),
+            KeyCode::Up if self.in_files_view() => self.files_scroll = self.files_scroll.saturating_sub(1),
+            KeyCode::PageUp if self.in_files_view() => self.files_scroll = self.files_scroll.saturating_sub(20),
+            KeyCode::Down if self.view_state == ViewState::Detail => self.scroll_detail(1),
+            KeyCode::Up if self.view_state == ViewState::Detail => {
+                self.detail_scroll = self.detail_scroll.saturating_sub(1);
+            }
+            KeyCode::PageDown if self.view_state == ViewState::Detail => self.scroll_detail(20),
+            KeyCode::PageUp if self.view_state == ViewState::Detail => {
+                self.detail_scroll = self.detail_scroll.saturating_sub(20);
+            }
+            // The detail views are left with Esc rather than cycled through
+            KeyCode::Left | KeyCode::Right if !VIEW_STATES.contains(&self.view_state) => {}
+            KeyCode::Char('/') if self.view_state == ViewState::Processes => {
+                self.open_prompt(PromptKind::Search);
+            }
+            KeyCode::Char('n') if self.view_state == ViewState::Processes => self.search_next(false),
+            KeyCode::Char('N') if self.view_state == ViewState::Processes => self.search_previous(),
+            KeyCode::Char('\\') if self.view_state == ViewState::Processes => {
+                self.open_prompt(PromptKind::Filter);
+                if let Some(prompt) = self.prompt.as_mut() {
+                    prompt.input = self.filter.as_ref().map(|f| f.as_str().to_string()).unwrap_or_default();
+                }
+            }
+            KeyCode::Char('H') if self.view_state == ViewState::Processes => {
+                self.show_threads = !self.show_threads;
+                self.options.read_threads = self.show_threads;
+                self.apply_filter();
+            }
+            // The detail process's threads are read anyway; only the table is toggled
+            KeyCode::Char('H') if self.view_state == ViewState::Detail => {
+                self.detail_threads = !self.detail_threads;
+            }
+            KeyCode::Char('C') if self.view_state == ViewState::Processes => {
+                self.toggle_cmdline();
+            }
+            KeyCode::Char('>') if self.view_state == ViewState::Processes => {
+                self.text_scroll += TEXT_SCROLL_STEP;
+            }
+            KeyCode::Char('<') if self.view_state == ViewState::Processes => {
+                self.text_scroll = self.text_scroll.saturating_sub(TEXT_SCROLL_STEP);
+            }
+            KeyCode::Char('I') => {
+                self.options.cpu_mode = match self.options.cpu_mode {
+                    CpuMode::Irix => CpuMode::Solaris,
+                    CpuMode::Solaris => CpuMode::Irix,
+                };
+            }
+            KeyCode::Char('v') if self.view_state == ViewState::Network => {
+                self.options.exclude_virtual_interfaces = !self.options.exclude_virtual_interfaces;
+            }
+            KeyCode::Char('M') => {
+                if self.view_state == ViewState::Memory {
+                    self.set_view(self.previous_view);
+                } else {
+                    self.previous_view = self.view_state;
+                    self.set_view(ViewState::Memory);
+                }
+            }
+            KeyCode::Left => {
+                self.set_view(VIEW_STATES[(self.view_i + VIEW_STATES.len() - 1) % VIEW_STATES.len()]);
+            }
+            KeyCode::Right => self.set_view(VIEW_STATES[(self.view_i + 1) % VIEW_STATES.len()]),
+            KeyCode::Char('l') if self.view_state == ViewState::Sockets => {
+                self.listening_only = !self.listening_only;
+                self.sockets_scroll = 0;
+            }
+            KeyCode::Char('x') if self.view_state == ViewState::Sockets => {
+                self.show_unix_sockets = !self.show_unix_sockets;
+                self.sockets_scroll = 0;
+            }
+            KeyCode::Down if self.view_state == ViewState::Sockets => {
+                let len = self.sockets().count();
+                self.sockets_scroll = (self.sockets_scroll + 1).min(len.saturating_sub(1));
+            }
+            KeyCode::Up if self.view_state == ViewState::Sockets => {
+                self.sockets_scroll = self.sockets_scroll.saturating_sub(1);
+            }
+            KeyCode::Down if self.view_state == ViewState::Processes => self.select_next(),
+            KeyCode::Up if self.view_state == ViewState::Processes => self.select_previous(),
+            KeyCode::Down if self.view_state == ViewState::Memory => {
+                let entries = self.snapshot.system.memory.entries.len();
+                self.memory_scroll = (self.memory_scroll + 1).min(entries.saturating_sub(1));
+            }
+            KeyCode::Up if self.view_state == ViewState::Memory => {
+                self.memory_scroll = self.memory_scroll.saturating_sub(1);
+            }
+            KeyCode::Char('c') if self.view_state == ViewState::Processes => {
+                self.set_sort(SortCriteria::CPU);
+            }
+            KeyCode::Char('m') if self.view_state == ViewState::Processes => {
+                self.set_sort(SortCriteria::Memory);
+            }
+            KeyCode::Char('p') if self.view_state == ViewState::Processes => {
+                self.set_sort(SortCriteria::PID);
+            }
+            KeyCode::Char('r') if self.view_state == ViewState::Processes => {
+                self.set_sort(SortCriteria::PR);
+            }
+            KeyCode::Char('P') if self.replay.is_some() => return Action::TogglePause,
+            KeyCode::Char('.') if self.replay.is_some() => return Action::StepForward,
+            KeyCode::Char(',') if self.replay.is_some() => return Action::StepBack,
+            KeyCode::Char('g') if self.replay.is_some() => self.open_prompt(PromptKind::Seek),
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn open_prompt(&mut self, kind: PromptKind) {
+        self.prompt = Some(Prompt {
+            kind,
+            input: String::new(),
+        });
+    }
+
+    fn handle_prompt_key(&mut self, code: KeyCode) -> Action {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return Action::None;
+        };
+        let kind = prompt.kind;
+        match code {
+            KeyCode::Esc => {
+                self.prompt = None;
+                if kind == PromptKind::Filter {
+                    self.set_filter("");
+                }
+                return Action::None;
+            }
+            KeyCode::Enter => {
+                let prompt = self.prompt.take().unwrap();
+                match prompt.kind {
+                    PromptKind::Seek => return Action::Seek(prompt.input),
+                    PromptKind::Search => {
+                        self.search = Some(prompt.input).filter(|s| !s.is_empty());
+                    }
+                    PromptKind::OpenFile => self.search_open_file(prompt.input),
+                    PromptKind::Filter => {
+                        self.set_filter(&prompt.input);
+                        // Stay in the prompt until the filter parses
+                        if self.filter_error.is_some() {
+                            self.prompt = Some(prompt);
+                        }
+                    }
+                }
+                return Action::None;
+            }
+            KeyCode::Backspace => {
+                prompt.input.pop();
+            }
+            KeyCode::Char(c) => prompt.input.push(c),
+            _ => return Action::None,
+        }
+
+        // Search and filter follow the text as it is typed
+        let input = prompt.input.clone();
+        match kind {
+            PromptKind::Seek | PromptKind::OpenFile => {}
+            PromptKind::Search => {
+                self.search = Some(input).filter(|s| !s.is_empty());
+                self.search_next(true);
+            }
+            PromptKind::Filter => self.set_filter(&input),
+        }
+        Action::None
+    }
+
+    /// Applies `text` as the filter if it parses, and otherwise keeps the
+    /// previous one and records the error for the help bar.
+    fn set_filter(&mut self, text: &str) {
+        if text.trim().is_empty() {
+            self.filter = None;
+            self.filter_error = None;
+        } else {
+            match text.parse::<Filter>() {
+                Ok(filter) => {
+                    self.filter = Some(filter);
+                    self.filter_error = None;
+                }
+                Err(e) => {
+                    self.filter_error = Some(e);
+                    return;
+                }
+            }
+        }
+        self.apply_filter();
+    }
+
+    /// Moves the selection to the next row matching the search, wrapping
+    /// around. `inclusive` lets the selected row itself match, so typing
+    /// more of a search keeps the selection where it is.
+    fn search_next(&mut self, inclusive: bool) {
+        let start = if inclusive { 0 } else { 1 };
+        let len = self.visible.len();
+        let found = (start..len + start)
+            .map(|step| (self.selected_index + step) % len.max(1))
+            .find(|&i| self.row_matches_search(i));
+        self.select_found(found);
+    }
+
+    fn search_previous(&mut self) {
+        let len = self.visible.len();
+        let found = (1..=len)
+            .map(|step| (self.selected_index + len - step) % len)
+            .find(|&i| self.row_matches_search(i));
+        self.select_found(found);
+    }
+
+    fn row_matches_search(&self, row: usize) -> bool {
+        match (&self.search, self.visible.get(row)) {
+            (Some(search), Some(&(i, None))) => matches_text(&self.snapshot.processes[i], search),
+            _ => false,
+        }
+    }
+
+    fn select_found(&mut self, found: Option<usize>) {
+        match (found, &self.search) {
+            (Some(row), _) => {
+                self.selected_index = row;
+                self.scroll_to_selection();
+            }
+            (None, Some(search)) => self.status = Some(format!("No match for '{}'", search)),
+            (None, None) => {}
+        }
+    }
+
+    fn handle_field_picker_key(&mut self, code: KeyCode) {
+        let Some(cursor) = self.field_picker else {
+            return;
+        };
+        let column = ALL_COLUMNS[cursor];
+        match code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('f') | KeyCode::Char('q') => {
+                self.field_picker = None;
+            }
+            KeyCode::Down => self.field_picker = Some((cursor + 1).min(ALL_COLUMNS.len() - 1)),
+            KeyCode::Up => self.field_picker = Some(cursor.saturating_sub(1)),
+            KeyCode::Char(' ') => {
+                if self.columns.contains(&column) {
+                    self.columns.retain(|c| *c != column);
+                } else {
+                    self.columns.push(column);
+                    self.columns.sort_by_key(|c| ALL_COLUMNS.iter().position(|a| a == c));
+                }
+                self.update_smaps();
+            }
+            KeyCode::Char('s') => match column.sort() {
+                Some(sort_criteria) => self.set_sort(sort_criteria),
+                None => self.status = Some(format!("Cannot sort by {}", column.title())),
+            },
+            _ => {}
+        }
+    }
+
+    /// Swaps the short command name for the full command line, or back.
+    fn toggle_cmdline(&mut self) {
+        for column in self.columns.iter_mut() {
+            *column = match *column {
+                Column::Command => Column::CommandLine,
+                Column::CommandLine => Column::Command,
+                other => other,
+            };
+        }
+        if !self.columns.iter().any(|c| matches!(c, Column::Command | Column::CommandLine)) {
+            self.columns.push(Column::CommandLine);
+        }
+        self.text_scroll = 0;
+    }
+
+    /// PSS/USS are only read while something shows or sorts by them.
+    fn update_smaps(&mut self) {
+        self.options.read_smaps = self.sort_criteria.needs_smaps()
+            || self.columns.iter().any(|c| matches!(c, Column::Pss | Column::Uss));
+    }
+
+    fn signal_targets(&mut self, signal: i32, verb: &str, done: &str) {
+        let targets = self.targets(verb);
+        let results = targets.iter().map(|t| signal::send(t.pid, signal)).collect();
+        self.report(&targets, results, verb, |what| format!("{} {}", done, what));
+    }
+
+    /// Whether the process controls are off, because the processes shown
+    /// are not the host's: a recording, or a captured `--root` tree whose
+    /// PIDs mean nothing here.
+    fn controls_refused(&mut self, verb: &str) -> bool {
+        let refused = if self.replay.is_some() {
+            "a recording"
+        } else if !self.source.is_live() {
+            "a captured /proc tree"
+        } else {
+            return false;
+        };
+        self.status = Some(format!("Cannot {} processes of {}", verb, refused));
+        true
+    }
+
+    /// The selected process, unless the controls are refused.
+    fn target(&mut self, verb: &str) -> Option<SignalTarget> {
+        if self.controls_refused(verb) {
+            return None;
+        }
+        let p = self.selected_process()?;
+        Some(SignalTarget {
+            pid: p.pid,
+            command: p.command.clone(),
+            user: p.user.clone(),
+        })
+    }
+
+    /// What the process controls act on: every tagged process, or the
+    /// selected one when nothing is tagged.
+    fn targets(&mut self, verb: &str) -> Vec<SignalTarget> {
+        if self.tagged.is_empty() {
+            return self.target(verb).into_iter().collect();
+        }
+        if self.controls_refused(verb) {
+            return Vec::new();
+        }
+        let mut targets: Vec<SignalTarget> = self
+            .snapshot
+            .processes
+            .iter()
+            .filter(|p| self.tagged.contains(&p.pid))
+            .map(|p| SignalTarget {
+                pid: p.pid,
+                command: p.command.clone(),
+                user: p.user.clone(),
+            })
+            .collect();
+        targets.sort_by_key(|t| t.pid);
+        targets
+    }
+
+    fn open_signal_menu(&mut self) {
+        let targets = self.targets("signal");
+        if targets.is_empty() {
+            return;
+        }
+        let cursor = all_signals().iter().position(|&(number, _)| number == SIGTERM).unwrap_or(0);
+        self.dialog = Some(Dialog::Signal { targets, cursor });
+    }
+
+    fn handle_dialog_key(&mut self, code: KeyCode) {
+        let Some(dialog) = self.dialog.take() else {
+            return;
+        };
+        self.dialog = match dialog {
+            Dialog::Signal { targets, cursor } => {
+                let signals = all_signals();
+                let last = signals.len() - 1;
+                match code {
+                    KeyCode::Esc | KeyCode::Char('q') => None,
+                    KeyCode::Enter => Some(Dialog::ConfirmSignal {
+                        targets,
+                        signal: signals[cursor].0,
+                    }),
+                    KeyCode::Down => Some(Dialog::Signal { targets, cursor: (cursor + 1).min(last) }),
+                    KeyCode::Up => Some(Dialog::Signal { targets, cursor: cursor.saturating_sub(1) }),
+                    KeyCode::PageDown => Some(Dialog::Signal { targets, cursor: (cursor + PAGE_ROWS).min(last) }),
+                    KeyCode::PageUp => Some(Dialog::Signal { targets, cursor: cursor.saturating_sub(PAGE_ROWS) }),
+                    KeyCode::Home => Some(Dialog::Signal { targets, cursor: 0 }),
+                    KeyCode::End => Some(Dialog::Signal { targets, cursor: last }),
+                    _ => Some(Dialog::Signal { targets, cursor }),
+                }
+            }
+            Dialog::ConfirmSignal { targets, signal } => match code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    let name = signal_name(signal);
+                    let results = targets.iter().map(|t| signal::send(t.pid, signal)).collect();
+                    let verb = format!("send {} to", name);
+                    self.report(&targets, results, &verb, |what| format!("Sent {} to {}", name, what));
+                    None
+                }
+                KeyCode::Char('n') | KeyCode::Esc | KeyCode::Char('q') => None,
+                _ => Some(Dialog::ConfirmSignal { targets, signal }),
+            },
+            Dialog::ConfirmShutdown { target, pids } => match code {
+                KeyCode::Char('y') | KeyCode::Enter => Some(Dialog::Shutdown(self.start_shutdown(target, pids))),
+                KeyCode::Char('n') | KeyCode::Esc | KeyCode::Char('q') => None,
+                _ => Some(Dialog::ConfirmShutdown { target, pids }),
+            },
+            Dialog::Shutdown(mut shutdown) => match code {
+                KeyCode::Esc | KeyCode::Char('q') if !shutdown.escalated => {
+                    self.status = Some(format!(
+                        "Stopped waiting; {} of {} processes still running after SIGTERM",
+                        shutdown.alive.len(),
+                        shutdown.pids.len()
+                    ));
+                    None
+                }
+                KeyCode::Char('k') if !shutdown.escalated => {
+                    shutdown.deadline = Instant::now();
+                    Some(Dialog::Shutdown(shutdown))
+                }
+                _ => Some(Dialog::Shutdown(shutdown)),
+            },
+            Dialog::Scheduler { targets, cursor, rt_priority } => {
+                let step = |by: i64| (rt_priority as i64 + by).clamp(1, RT_PRIORITY_MAX as i64) as u32;
+                match code {
+                    KeyCode::Esc | KeyCode::Char('q') => None,
+                    KeyCode::Enter => {
+                        let policy = POLICIES[cursor];
+                        let results = targets.iter().map(|t| sched::set_policy(t.pid, policy, rt_priority)).collect();
+                        self.report(&targets, results, "reschedule", |what| {
+                            if policy.is_realtime() {
+                                format!("Set {} to {} priority {}", what, policy.name(), rt_priority)
+                            } else {
+                                format!("Set {} to {}", what, policy.name())
+                            }
+                        });
+                        None
+                    }
+                    KeyCode::Down => Some(Dialog::Scheduler {
+                        targets,
+                        cursor: (cursor + 1).min(POLICIES.len() - 1),
+                        rt_priority,
+                    }),
+                    KeyCode::Up => Some(Dialog::Scheduler { targets, cursor: cursor.saturating_sub(1), rt_priority }),
+                    KeyCode::Right => Some(Dialog::Scheduler { targets, cursor, rt_priority: step(1) }),
+                    KeyCode::Left => Some(Dialog::Scheduler { targets, cursor, rt_priority: step(-1) }),
+                    KeyCode::PageUp => Some(Dialog::Scheduler { targets, cursor, rt_priority: step(10) }),
+                    KeyCode::PageDown => Some(Dialog::Scheduler { targets, cursor, rt_priority: step(-10) }),
+                    _ => Some(Dialog::Scheduler { targets, cursor, rt_priority }),
+                }
+            }
+            Dialog::IoPriority { targets, cursor, level } => match code {
+                KeyCode::Esc | KeyCode::Char('q') => None,
+                KeyCode::Enter => {
+                    let class = IO_CLASSES[cursor];
+                    let results = targets.iter().map(|t| sched::set_io_priority(t.pid, class, level)).collect();
+                    self.report(&targets, results, "change the I/O priority of", |what| {
+                        if class.has_level() {
+                            format!("Set I/O priority of {} to {} {}", what, class.name(), level)
+                        } else {
+                            format!("Set I/O priority of {} to {}", what, class.name())
+                        }
+                    });
+                    None
+                }
+                KeyCode::Down => Some(Dialog::IoPriority {
+                    targets,
+                    cursor: (cursor + 1).min(IO_CLASSES.len() - 1),
+                    level,
+                }),
+                KeyCode::Up => Some(Dialog::IoPriority { targets, cursor: cursor.saturating_sub(1), level }),
+                KeyCode::Right => Some(Dialog::IoPriority { targets, cursor, level: (level + 1).min(IO_LEVEL_MAX) }),
+                KeyCode::Left => Some(Dialog::IoPriority { targets, cursor, level: level.saturating_sub(1) }),
+                _ => Some(Dialog::IoPriority { targets, cursor, level }),
+            },
+            Dialog::Affinity { targets, mut cpus, cursor } => match code {
+                KeyCode::Esc | KeyCode::Char('q') => None,
+                KeyCode::Enter if !cpus.contains(&true) => {
+                    self.status = Some("Pick at least one CPU".to_string());
+                    Some(Dialog::Affinity { targets, cpus, cursor })
+                }
+                KeyCode::Enter => {
+                    let results = targets.iter().map(|t| sched::set_affinity(t.pid, &cpus)).collect();
+                    self.report(&targets, results, "change the CPU affinity of", |what| {
+                        format!("Set CPU affinity of {}", what)
+                    });
+                    None
+                }
+                KeyCode::Char(' ') => {
+                    cpus[cursor] = !cpus[cursor];
+                    Some(Dialog::Affinity { targets, cpus, cursor })
+                }
+                KeyCode::Char('a') => {
+                    let all = !cpus.iter().all(|&allowed| allowed);
+                    cpus.iter_mut().for_each(|allowed| *allowed = all);
+                    Some(Dialog::Affinity { targets, cpus, cursor })
+                }
+                KeyCode::Down => {
+                    let cursor = (cursor + 1).min(cpus.len() - 1);
+                    Some(Dialog::Affinity { targets, cpus, cursor })
+                }
+                KeyCode::Up => Some(Dialog::Affinity { targets, cpus, cursor: cursor.saturating_sub(1) }),
+                KeyCode::PageDown => {
+                    let cursor = (cursor + PAGE_ROWS).min(cpus.len() - 1);
+                    Some(Dialog::Affinity { targets, cpus, cursor })
+                }
+                KeyCode::PageUp => {
+                    let cursor = cursor.saturating_sub(PAGE_ROWS);
+                    Some(Dialog::Affinity { targets, cpus, cursor })
+                }
+                _ => Some(Dialog::Affinity { targets, cpus, cursor }),
+            },
+        };
+    }
+
+    /// Sums up one action over `targets` in the status line. `done` words
+    /// the success given what it applied to (`123 (sleep)`, `3 processes`);
+    /// failures are listed by PID after it.
+    fn report(
+        &mut self,
+        targets: &[SignalTarget],
+        results: Vec<io::Result<()>>,
+        verb: &str,
+        done: impl FnOnce(&str) -> String,
+    ) {
+        let failed: Vec<(i32, &io::Error)> = targets
+            .iter()
+            .zip(&results)
+            .filter_map(|(t, result)| result.as_ref().err().map(|e| (t.pid, e)))
+            .collect();
+        let status = match (targets, failed.as_slice()) {
+            ([], _) => return,
+            ([target], []) => done(&format!("{} ({})", target.pid, target.command)),
+            ([target], [(_, e)]) => format!("Failed to {} {} ({}): {}", verb, target.pid, target.command, e),
+            (_, []) => done(&format!("{} processes", targets.len())),
+            (_, failed) => {
+                let succeeded = targets.len() - failed.len();
+                let failures: Vec<String> = failed.iter().map(|(pid, e)| format!("{} ({})", pid, e)).collect();
+                format!(
+                    "{}; failed for {}",
+                    done(&format!("{} of {} processes", succeeded, targets.len())),
+                    failures.join(", ")
+                )
+            }
+        };
+        self.status = Some(status);
+    }
+
+    /// Moves the nice value of the targets by `by`. New values are written
+    /// into the snapshot so repeated presses add up before the next refresh.
+    fn renice_targets(&mut self, by: i64) {
+        let targets = self.targets("renice");
+        let mut results = Vec::new();
+        for target in &targets {
+            let Some(process) = self.snapshot.processes.iter_mut().find(|p| p.pid == target.pid) else {
+                results.push(Err(io::Error::from_raw_os_error(libc::ESRCH)));
+                continue;
+            };
+            let nice = (process.nice + by).clamp(NICE_MIN as i64, NICE_MAX as i64);
+            let result = sched::set_nice(target.pid, nice as i32);
+            if result.is_ok() {
+                process.nice = nice;
+            }
+            results.push(result);
+        }
+        let nice = match targets.as_slice() {
+            [target] => self.snapshot.processes.iter().find(|p| p.pid == target.pid).map(|p| p.nice),
+            _ => None,
+        };
+        self.report(&targets, results, "renice", |what| match nice {
+            Some(nice) => format!("Reniced {} to {}", what, nice),
+            None => format!("Reniced {} by {:+}", what, by),
+        });
+    }
+
+    fn open_scheduler(&mut self) {
+        let targets = self.targets("reschedule");
+        let Some(first) = targets.first() else {
+            return;
+        };
+        // Start from the first target's current settings
+        let Some(process) = self.snapshot.processes.iter().find(|p| p.pid == first.pid) else {
+            return;
+        };
+        let current = Policy::from_raw(process.policy).unwrap_or(Policy::Other);
+        let cursor = POLICIES.iter().position(|&p| p == current).unwrap_or(0);
+        let rt_priority = process.rt_priority.max(1);
+        self.dialog = Some(Dialog::Scheduler { targets, cursor, rt_priority });
+    }
+
+    fn open_io_priority(&mut self) {
+        let targets = self.targets("change");
+        let Some(first) = targets.first() else {
+            return;
+        };
+        match sched::io_priority(first.pid) {
+            Ok((class, level)) => {
+                let cursor = IO_CLASSES.iter().position(|&c| c == class).unwrap_or(0);
+                // Best effort is the class with a level most people want
+                let level = if class.has_level() { level } else { 4 };
+                self.dialog = Some(Dialog::IoPriority { targets, cursor, level });
+            }
+            Err(e) => self.status = Some(format!("Failed to read the I/O priority of {}: {}", first.pid, e)),
+        }
+    }
+
+    fn open_affinity(&mut self) {
+        let targets = self.targets("change");
+        let Some(first) = targets.first() else {
+            return;
+        };
+        match sched::affinity(first.pid) {
+            Ok(cpus) => self.dialog = Some(Dialog::Affinity { targets, cpus, cursor: 0 }),
+            Err(e) => self.status = Some(format!("Failed to read the CPU affinity of {}: {}", first.pid, e)),
+        }
+    }
+
+    /// Tags or untags the selected process.
+    fn toggle_tag(&mut self) {
+        let Some(pid) = self.selected_process().map(|p| p.pid) else {
+            return;
+        };
+        if !self.tagged.remove(&pid) {
+            self.tagged.insert(pid);
+        }
+    }
+
+    /// Tags the selected process and everything below it.
+    fn tag_subtree(&mut self) {
+        let Some(pid) = self.selected_process().map(|p| p.pid) else {
+            return;
+        };
+        let mut stack = vec![pid];
+        while let Some(pid) = stack.pop() {
+            if self.tagged.insert(pid) {
+                stack.extend(self.snapshot.processes.iter().filter(|p| p.ppid == pid && p.pid != pid).map(|p| p.pid));
+            }
+        }
+    }
+
+    /// Tags every process the filter lets through.
+    fn tag_filtered(&mut self) {
+        let Some(filter) = &self.filter else {
+            self.status = Some("No filter set; \\ sets one".to_string());
+            return;
+        };
+        let matching: Vec<i32> = self.snapshot.processes.iter().filter(|p| filter.matches(p)).map(|p| p.pid).collect();
+        self.status = Some(format!("Tagged {} processes matching '{}'", matching.len(), filter.as_str()));
+        self.tagged.extend(matching);
+    }
+
+    /// Asks before shutting down the selected process and its descendants.
+    fn confirm_shutdown(&mut self) {
+        let Some(target) = self.target("signal") else {
+            return;
+        };
+        match subtree_bottom_up(&self.source, target.pid) {
+            Ok(mut pids) => {
+                // Our own process can be in the subtree of a shell we run under
+                pids.retain(|&pid| pid != std::process::id() as i32);
+                self.dialog = Some(Dialog::ConfirmShutdown { target, pids });
+            }
+            Err(e) => self.status = Some(format!("Failed to read the process tree: {}", e)),
+        }
+    }
+
+    fn start_shutdown(&mut self, target: SignalTarget, pids: Vec<i32>) -> Shutdown {
+        let mut sent = Vec::new();
+        let mut denied = Vec::new();
+        for pid in pids {
+            match signal::send(pid, SIGTERM) {
+                Ok(()) => sent.push(pid),
+                Err(e) if e.raw_os_error() == Some(libc::EPERM) => denied.push(pid),
+                // Already gone
+                Err(_) => {}
+            }
+        }
+        Shutdown {
+            target,
+            alive: sent.clone(),
+            pids: sent,
+            denied,
+            deadline: Instant::now() + self.kill_grace,
+            escalated: false,
+        }
+    }
+
+    /// Moves a subtree shutdown along. Called on every pass of the main
+    /// loop; returns whether the screen needs redrawing.
+    pub fn tick(&mut self) -> bool {
+        let Some(Dialog::Shutdown(shutdown)) = &mut self.dialog else {
+            return false;
+        };
+        let source = &self.source;
+        shutdown.alive.retain(|&pid| is_running(source, pid));
+        let now = Instant::now();
+        let finished = if shutdown.alive.is_empty() {
+            Some(format!("Shut down {} processes", shutdown.pids.len()))
+        } else if now < shutdown.deadline {
+            None
+        } else if !shutdown.escalated {
+            for &pid in &shutdown.alive {
+                let _ = signal::send(pid, SIGKILL);
+            }
+            shutdown.escalated = true;
+            shutdown.deadline = now + KILL_WAIT;
+            None
+        } else {
+            Some(format!(
+                "{} processes survived SIGKILL: {}",
+                shutdown.alive.len(),
+                join_pids(&shutdown.alive)
+            ))
+        };
+        if let Some(mut message) = finished {
+            if !shutdown.denied.is_empty() {
+                message.push_str(&format!("; not permitted to signal {}", join_pids(&shutdown.denied)));
+            }
+            self.status = Some(message);
+            self.dialog = None;
+        }
+        true
+    }
+
+    /// Shows `view` (the detail pane or open files) for the selected process.
+    fn open_detail(&mut self, view: ViewState) {
+        if let Some(pid) = self.selected_process().map(|p| p.pid) {
+            self.options.detail_pid = Some(pid);
+            self.detail_scroll = 0;
+            self.files_scroll = 0;
+            self.detail_from = self.view_state;
+            self.files_from = self.view_state;
+            self.set_view(view);
+        }
+    }
+
+    /// The process the detail views are showing, while it is alive.
+    pub fn detail_process(&self) -> Option<&Process> {
+        let pid = self.options.detail_pid?;
+        self.snapshot.processes.iter().find(|p| p.pid == pid)
+    }
+
+    fn close_detail(&mut self) {
+        self.options.detail_pid = None;
+        self.set_view(self.detail_from);
+    }
+
+    /// The list and the tree, where the per-process keys work.
+    fn in_process_view(&self) -> bool {
+        matches!(self.view_state, ViewState::Processes | ViewState::ProcessTree)
+    }
+
+    /// The process tree in display order, skipping collapsed subtrees.
+    /// Siblings keep the order of the process table, so they follow the
+    /// current sort.
+    pub fn tree_rows(&self) -> Vec<TreeRow> {
+        let processes = &self.snapshot.processes;
+        let pids: HashSet<i32> = processes.iter().map(|p| p.pid).collect();
+        let mut children: HashMap<i32, Vec<usize>> = HashMap::new();
+        let mut roots = Vec::new();
+        for (i, p) in processes.iter().enumerate() {
+            if p.ppid != p.pid && pids.contains(&p.ppid) {
+                children.entry(p.ppid).or_default().push(i);
+            } else {
+                roots.push(i);
+            }
+        }
+        let mut rows = Vec::new();
+        self.push_tree_rows(&roots, &children, None, &mut rows);
+        rows
+    }
+
+    // `guide` is the prefix drawn for the ancestors' columns; `None` for roots
+    fn push_tree_rows(
+        &self,
+        siblings: &[usize],
+        children: &HashMap<i32, Vec<usize>>,
+        guide: Option<&str>,
+        rows: &mut Vec<TreeRow>,
+    ) {
+        for (n, &index) in siblings.iter().enumerate() {
+            let last = n + 1 == siblings.len();
+            let pid = self.snapshot.processes[index].pid;
+            let kids = children.get(&pid);
+            let collapsed = self.collapsed.contains(&pid);
+            rows.push(TreeRow {
+                index,
+                prefix: guide.map_or(String::new(), |g| format!("{}{}", g, if last { "└─ " } else { "├─ " })),
+                has_children: kids.is_some(),
+                collapsed,
+            });
+            if let (Some(kids), false) = (kids, collapsed) {
+                let child_guide = guide.map_or(String::new(), |g| format!("{}{}", g, if last { "   " } else { "│  " }));
+                self.push_tree_rows(kids, children, Some(&child_guide), rows);
+            }
+        }
+    }
+
+    /// Row of the highlighted node in `rows`, or the first row if it's gone.
+    pub fn tree_position(&self, rows: &[TreeRow]) -> usize {
+        self.tree_view_pid
+            .and_then(|pid| rows.iter().position(|r| self.snapshot.processes[r.index].pid == pid))
+            .unwrap_or(0)
+    }
+
+    fn move_in_tree(&mut self, delta: isize) {
+        let rows = self.tree_rows();
+        if rows.is_empty() {
+            return;
+        }
+        let position = self
+            .tree_position(&rows)
+            .saturating_add_signed(delta)
+            .min(rows.len() - 1);
+        self.tree_view_pid = Some(self.snapshot.processes[rows[position].index].pid);
+        self.tree_scroll = scroll_to(position, self.tree_scroll);
+    }
+
+    /// Highlights `pid` in the tree, expanding its ancestors.
+    fn show_in_tree(&mut self, pid: i32) {
+        let parents: HashMap<i32, i32> = self.snapshot.processes.iter().map(|p| (p.pid, p.ppid)).collect();
+        let mut ancestor = parents.get(&pid).copied();
+        // Bounded so a PID 0 parent loop can't spin forever
+        for _ in 0..parents.len() {
+            let Some(ppid) = ancestor else {
+                break;
+            };
+            self.collapsed.remove(&ppid);
+            ancestor = parents.get(&ppid).copied();
+        }
+        self.tree_view_pid = Some(pid);
+        let rows = self.tree_rows();
+        self.tree_scroll = scroll_to(self.tree_position(&rows), self.tree_scroll);
+    }
+
+    fn search_open_file(&mut self, query: String) {
+        if query.is_empty() {
+            return;
+        }
+        self.file_users = find_open_file(&self.source, &query);
+        self.file_query = query;
+        self.files_scroll = 0;
+        if self.view_state != ViewState::FileSearch {
+            self.previous_view = self.view_state;
+            self.set_view(ViewState::FileSearch);
+        }
+    }
+
+    fn in_files_view(&self) -> bool {
+        matches!(self.view_state, ViewState::Files | ViewState::FileSearch)
+    }
+
+    fn scroll_files(&mut self, rows: usize) {
+        let len = match self.view_state {
+            ViewState::FileSearch => self.file_users.len(),
+            _ => self.snapshot.detail.as_ref().map_or(0, |d| d.open_files.len()),
+        };
+        self.files_scroll = (self.files_scroll + rows).min(len.saturating_sub(1));
+    }
+
+    fn scroll_detail(&mut self, lines: usize) {
+        let tasks = self.detail_process().map_or(&[][..], |p| &p.tasks);
+        let len = match &self.snapshot.detail {
+            Some(detail) => process_detail_lines(detail, self.detail_threads.then_some(tasks)).len(),
+            None => 0,
+        };
+        self.detail_scroll = (self.detail_scroll + lines).min(len.saturating_sub(1));
+    }
+
+    fn scroll_to_selection(&mut self) {
+        self.scroll_offset = scroll_to(self.selected_index, self.scroll_offset);
+    }
+
+    fn select_next(&mut self) {
+        if self.selected_index + 1 < self.visible.len() {
+            self.selected_index += 1;
+            if self.selected_index >= self.scroll_offset + PAGE_ROWS {
+                self.scroll_offset += 1;
+            }
+        }
+    }
+
+    fn select_previous(&mut self) {
+        if self.selected_index > 0 {
+            self.selected_index -= 1;
+            if self.selected_index < self.scroll_offset {
+                self.scroll_offset -= 1;
+            }
+        }
+    }
+
+    fn set_view(&mut self, view_state: ViewState) {
+        self.view_state = view_state;
+        self.view_i = VIEW_STATES.iter().position(|v| *v == view_state).unwrap_or(0);
+        // Sockets are only worth the fd scan while they are on screen
+        self.options.read_sockets = view_state == ViewState::Sockets;
+    }
+
+    fn set_sort(&mut self, sort_criteria: SortCriteria) {
+        self.sort_criteria = sort_criteria;
+        self.update_smaps();
+        self.sort();
+    }
+}
+
+fn join_pids(pids: &[i32]) -> String {
+    pids.iter().map(|pid| pid.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// First row to show so that `row` is inside a `PAGE_ROWS` window that
+/// currently starts at `scroll`.
+pub fn scroll_to(row: usize, scroll: usize) -> usize {
+    if row < scroll {
+        row
+    } else if row >= scroll + PAGE_ROWS {
+        row + 1 - PAGE_ROWS
+    } else {
+        scroll
+    }
+}
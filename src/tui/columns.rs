@@ -0,0 +1,215 @@
+//! Columns of the process table, picked with the field picker (`f`).
+
+use crate::format::format_bytes;
+use os_project::sched::Policy;
+use os_project::{Process, SortCriteria, ThreadInfo};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Column {
+    Pid,
+    Ppid,
+    User,
+    State,
+    Threads,
+    Priority,
+    Nice,
+    Policy,
+    Affinity,
+    Cpu,
+    Virtual,
+    Resident,
+    Shared,
+    Swap,
+    MemPercent,
+    Pss,
+    Uss,
+    Time,
+    Command,
+    CommandLine,
+    Exe,
+}
+
+/// Every column, in display order.
+pub const ALL_COLUMNS: [Column; 21] = [
+    Column::Pid,
+    Column::Ppid,
+    Column::User,
+    Column::State,
+    Column::Threads,
+    Column::Priority,
+    Column::Nice,
+    Column::Policy,
+    Column::Affinity,
+    Column::Cpu,
+    Column::Virtual,
+    Column::Resident,
+    Column::Shared,
+    Column::Swap,
+    Column::MemPercent,
+    Column::Pss,
+    Column::Uss,
+    Column::Time,
+    Column::Command,
+    Column::CommandLine,
+    Column::Exe,
+];
+
+/// Columns shown until the user picks others.
+pub const DEFAULT_COLUMNS: [Column; 13] = [
+    Column::Pid,
+    Column::Ppid,
+    Column::User,
+    Column::State,
+    Column::Threads,
+    Column::Priority,
+    Column::Nice,
+    Column::Policy,
+    Column::Affinity,
+    Column::Cpu,
+    Column::Resident,
+    Column::Time,
+    Column::Command,
+];
+
+impl Column {
+    pub fn title(self) -> &'static str {
+        match self {
+            Column::Pid => "PID",
+            Column::Ppid => "PPID",
+            Column::User => "USER",
+            Column::State => "ST",
+            Column::Threads => "THR",
+            Column::Priority => "PR",
+            Column::Nice => "NI",
+            Column::Policy => "POL",
+            Column::Affinity => "CPUS",
+            Column::Cpu => "%CPU",
+            Column::Virtual => "VIRT",
+            Column::Resident => "RES",
+            Column::Shared => "SHR",
+            Column::Swap => "SWAP",
+            Column::MemPercent => "%MEM",
+            Column::Pss => "PSS",
+            Column::Uss => "USS",
+            Column::Time => "TIME+",
+            Column::Command | Column::CommandLine => "COMMAND",
+            Column::Exe => "EXE",
+        }
+    }
+
+    /// Longer name for the field picker.
+    pub fn description(self) -> &'static str {
+        match self {
+            Column::Pid => "Process ID",
+            Column::Ppid => "Parent process ID",
+            Column::User => "Real user",
+            Column::State => "Process state",
+            Column::Threads => "Number of threads",
+            Column::Priority => "Kernel priority",
+            Column::Nice => "Nice value",
+            Column::Policy => "Scheduling policy (and real-time priority)",
+            Column::Affinity => "CPUs the process may run on",
+            Column::Cpu => "CPU usage",
+            Column::Virtual => "Virtual memory size",
+            Column::Resident => "Resident memory",
+            Column::Shared => "Shared resident memory (file + shmem)",
+            Column::Swap => "Swapped-out memory",
+            Column::MemPercent => "Resident memory as % of RAM",
+            Column::Pss => "Proportional set size (smaps_rollup)",
+            Column::Uss => "Unique set size (smaps_rollup)",
+            Column::Time => "CPU time",
+            Column::Command => "Command name",
+            Column::CommandLine => "Full command line",
+            Column::Exe => "Executable path",
+        }
+    }
+
+    pub fn width(self) -> u16 {
+        match self {
+            Column::Pid | Column::Ppid => 7,
+            Column::User => 10,
+            Column::State => 3,
+            Column::Threads | Column::Priority | Column::Nice => 5,
+            Column::Policy => 7,
+            Column::Cpu | Column::MemPercent => 6,
+            Column::Time => 12,
+            Column::Command | Column::CommandLine | Column::Exe => 20,
+            _ => 10,
+        }
+    }
+
+    /// The sort order this column maps to, if it can be sorted on.
+    pub fn sort(self) -> Option<SortCriteria> {
+        match self {
+            Column::Pid => Some(SortCriteria::PID),
+            Column::Priority => Some(SortCriteria::PR),
+            Column::Cpu => Some(SortCriteria::CPU),
+            Column::Virtual => Some(SortCriteria::Virtual),
+            Column::Resident => Some(SortCriteria::Memory),
+            Column::Shared => Some(SortCriteria::Shared),
+            Column::Swap => Some(SortCriteria::Swap),
+            Column::MemPercent => Some(SortCriteria::MemPercent),
+            Column::Pss => Some(SortCriteria::Pss),
+            Column::Uss => Some(SortCriteria::Uss),
+            _ => None,
+        }
+    }
+
+    pub fn value(self, p: &Process) -> String {
+        match self {
+            Column::Pid => p.pid.to_string(),
+            Column::Ppid => p.ppid.to_string(),
+            Column::User => p.user.clone(),
+            Column::State => p.state.to_string(),
+            Column::Threads => p.threads.to_string(),
+            Column::Priority => p.priority.to_string(),
+            Column::Nice => p.nice.to_string(),
+            Column::Policy => match Policy::from_raw(p.policy) {
+                Some(policy) if policy.is_realtime() => format!("{} {}", policy.name(), p.rt_priority),
+                Some(policy) => policy.name().to_string(),
+                None => p.policy.to_string(),
+            },
+            Column::Affinity => p.cpus_allowed.clone(),
+            Column::Cpu => format!("{:.1}", p.cpu_usage),
+            Column::Virtual => format_kb(p.virt_kb),
+            Column::Resident => format_kb(p.res_kb),
+            Column::Shared => format_kb(p.shr_kb),
+            Column::Swap => format_kb(p.swap_kb),
+            Column::MemPercent => format!("{:.1}", p.mem_percent),
+            Column::Pss => p.pss_kb.map(format_kb).unwrap_or_else(|| "-".to_string()),
+            Column::Uss => p.uss_kb.map(format_kb).unwrap_or_else(|| "-".to_string()),
+            Column::Time => p.time_plus.clone(),
+            Column::Command => p.command.clone(),
+            Column::CommandLine => p.cmdline.clone(),
+            Column::Exe => p.exe.clone().unwrap_or_else(|| "-".to_string()),
+        }
+    }
+
+    /// The value for a thread row under process `p`. Figures that only
+    /// exist per process are left blank.
+    pub fn thread_value(self, p: &Process, t: &ThreadInfo, last: bool) -> String {
+        match self {
+            Column::Pid => t.tid.to_string(),
+            Column::Ppid => p.pid.to_string(),
+            Column::User => p.user.clone(),
+            Column::State => t.state.to_string(),
+            Column::Priority => p.priority.to_string(),
+            Column::Nice => p.nice.to_string(),
+            Column::Cpu => format!("{:.1}", t.cpu_usage),
+            Column::Command | Column::CommandLine | Column::Exe => {
+                format!("{} {}", if last { "└─" } else { "├─" }, t.name)
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// Free-form text columns, which take the spare width and scroll
+    /// sideways.
+    pub fn is_text(self) -> bool {
+        matches!(self, Column::Command | Column::CommandLine | Column::Exe)
+    }
+}
+
+fn format_kb(kb: u64) -> String {
+    format_bytes(kb as f64 * 1024.0)
+}
@@ -0,0 +1,48 @@
+pub mod app;
+pub mod columns;
+pub mod ui;
+
+use ratatui::{backend::CrosstermBackend, Terminal};
+use std::io::Stdout;
+use std::ops::{Deref, DerefMut};
+
+/// The terminal in raw mode. Dropping it restores the terminal, so an early
+/// return or a panic does not leave the shell unusable.
+pub struct TerminalGuard {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl Deref for TerminalGuard {
+    type Target = Terminal<CrosstermBackend<Stdout>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.terminal
+    }
+}
+
+impl DerefMut for TerminalGuard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.terminal
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        // Clear the terminal before exiting
+        print!("\x1B[2J\x1B[H");
+        // Nothing left to report these to
+        let _ = crossterm::terminal::disable_raw_mode();
+        let _ = self.terminal.show_cursor();
+    }
+}
+
+pub fn setup_terminal() -> os_project::Result<TerminalGuard> {
+    crossterm::terminal::enable_raw_mode()?;
+    match Terminal::new(CrosstermBackend::new(std::io::stdout())) {
+        Ok(terminal) => Ok(TerminalGuard { terminal }),
+        Err(e) => {
+            let _ = crossterm::terminal::disable_raw_mode();
+            Err(e.into())
+        }
+    }
+}
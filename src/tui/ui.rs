@@ -0,0 +1,963 @@
+use super::app::{scroll_to, App, Dialog, PromptKind, Shutdown, SignalTarget, ViewState, PAGE_ROWS};
+use super::columns::ALL_COLUMNS;
+use crate::format::{format_bytes, format_system_stats};
+use os_project::cpu::CpuUsage;
+use os_project::detail::ProcessDetail;
+use os_project::disk::DiskStats;
+use os_project::memory::MemoryStats;
+use os_project::network::InterfaceStats;
+use os_project::sched::{IO_CLASSES, POLICIES};
+use os_project::signal::{all_signals, signal_name};
+use os_project::sockets::{tcp_state_counts, Protocol};
+use os_project::{CpuMode, Snapshot, SortCriteria, ThreadInfo};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, LineGauge, Paragraph, Row, Table},
+};
+
+pub fn draw(f: &mut ratatui::Frame, app: &App) {
+    let stats = format_system_stats(&app.snapshot);
+    let help = help_text(app);
+    // Header and help bar take what their text needs, the view gets the rest
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(stats.lines().count() as u16 + 2),
+                Constraint::Min(5),
+                Constraint::Length(help.lines().count() as u16 + 2),
+            ]
+            .as_ref(),
+        )
+        .split(f.area());
+
+    draw_system_stats(f, chunks[0], &app.snapshot, stats);
+
+    match app.view_state {
+        ViewState::Processes => {
+            draw_process_list(f, chunks[1], app);
+            if let Some(cursor) = app.field_picker {
+                draw_field_picker(f, chunks[1], app, cursor);
+            }
+        }
+        ViewState::CrashTracking => {
+            draw_crash_tracking(f, chunks[1], &app.snapshot.crash_events);
+        }
+        ViewState::ProcessTree => draw_process_tree(f, chunks[1], app),
+        ViewState::Network => {
+            draw_network(f, chunks[1], &app.snapshot.system.network, app.options.exclude_virtual_interfaces);
+        }
+        ViewState::Sockets => draw_sockets(f, chunks[1], app),
+        ViewState::Disks => {
+            draw_disks(f, chunks[1], &app.snapshot.system.disks);
+        }
+        ViewState::Detail => draw_process_detail(f, chunks[1], app),
+        ViewState::Files => draw_open_files(f, chunks[1], app),
+        ViewState::FileSearch => draw_file_search(f, chunks[1], app),
+        ViewState::Memory => {
+            draw_memory(f, chunks[1], &app.snapshot.system.memory, app.memory_scroll);
+        }
+    }
+
+    match &app.dialog {
+        Some(Dialog::Signal { targets, cursor }) => {
+            let items = all_signals()
+                .into_iter()
+                .map(|(number, name)| format!("{:>3} {}", number, name))
+                .collect();
+            let title = format!("Send signal to {}", targets_label(targets));
+            draw_menu(f, chunks[1], title, 34, items, *cursor, "Enter: Send  Esc: Cancel");
+        }
+        Some(Dialog::Scheduler { targets, cursor, rt_priority }) => {
+            let items = POLICIES
+                .iter()
+                .map(|policy| {
+                    let priority = if policy.is_realtime() { format!("priority {}", rt_priority) } else { String::new() };
+                    format!("{:<4}{:<44}{}", policy.name(), policy.description(), priority)
+                })
+                .collect();
+            let title = format!("Scheduling policy of {}", targets_label(targets));
+            draw_menu(f, chunks[1], title, 64, items, *cursor, "Enter: Set  ←/→ PgUp/PgDn: RT priority  Esc: Cancel");
+        }
+        Some(Dialog::IoPriority { targets, cursor, level }) => {
+            let items = IO_CLASSES
+                .iter()
+                .map(|class| {
+                    let level = if class.has_level() { format!("level {}", level) } else { String::new() };
+                    format!("{:<14}{}", class.name(), level)
+                })
+                .collect();
+            let title = format!("I/O priority of {}", targets_label(targets));
+            draw_menu(f, chunks[1], title, 44, items, *cursor, "Enter: Set  ←/→: Level  Esc: Cancel");
+        }
+        Some(Dialog::Affinity { targets, cpus, cursor }) => {
+            let items = cpus
+                .iter()
+                .enumerate()
+                .map(|(cpu, &allowed)| format!("{} CPU {}", if allowed { "[x]" } else { "[ ]" }, cpu))
+                .collect();
+            let title = format!("CPU affinity of {}", targets_label(targets));
+            draw_menu(f, chunks[1], title, 50, items, *cursor, "space: Toggle  a: All  Enter: Set  Esc: Cancel");
+        }
+        Some(Dialog::ConfirmSignal { targets, signal }) => {
+            let question = Line::from(vec![
+                Span::raw("Send "),
+                Span::styled(format!("{} ({})", signal_name(*signal), signal), warning_style()),
+                Span::raw(" to"),
+            ]);
+            draw_confirm(f, chunks[1], question, targets, Vec::new());
+        }
+        Some(Dialog::ConfirmShutdown { target, pids }) => {
+            let question = Line::from(vec![
+                Span::raw("Shut down "),
+                Span::styled(format!("{} processes", pids.len()), warning_style()),
+                Span::raw(" in the subtree of"),
+            ]);
+            let note = format!(
+                "SIGTERM children first, SIGKILL after {}s",
+                app.kill_grace.as_secs_f64()
+            );
+            draw_confirm(f, chunks[1], question, std::slice::from_ref(target), vec![Line::raw(note)]);
+        }
+        Some(Dialog::Shutdown(shutdown)) => draw_shutdown(f, chunks[1], app, shutdown),
+        None => {}
+    }
+
+    draw_help_section(f, chunks[2], help);
+}
+
+fn draw_system_stats(f: &mut ratatui::Frame, area: ratatui::layout::Rect, snapshot: &Snapshot, stats: String) {
+    let block = Block::default().title("System Stats").borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)].as_ref())
+        .split(inner);
+
+    let paragraph = Paragraph::new(stats);
+    f.render_widget(paragraph, columns[0]);
+    draw_cpu_bars(f, columns[1], &snapshot.system.cpu.cores);
+}
+
+// One htop-style meter per core, laid out in as many columns as needed
+fn draw_cpu_bars(f: &mut ratatui::Frame, area: ratatui::layout::Rect, cores: &[CpuUsage]) {
+    if cores.is_empty() || area.height == 0 {
+        return;
+    }
+    let rows = area.height as usize;
+    let columns = cores.len().div_ceil(rows);
+    let column_width = area.width as usize / columns;
+    // "NN [" + "] 100.0%" around the bar
+    let bar_width = column_width.saturating_sub(14);
+
+    let mut lines: Vec<Vec<Span>> = vec![Vec::new(); rows.min(cores.len())];
+    for (i, core) in cores.iter().enumerate() {
+        let line = &mut lines[i % rows];
+        let segments = [
+            (core.nice, Color::Blue),
+            (core.user, Color::Green),
+            (core.system + core.irq + core.softirq, Color::Red),
+            (core.steal, Color::Cyan),
+        ];
+        line.push(Span::raw(format!("{:>3} [", i)));
+        let mut used = 0;
+        for (percent, color) in segments {
+            let cells = ((percent / 100.0 * bar_width as f64).round() as usize).min(bar_width - used);
+            line.push(Span::styled("|".repeat(cells), Style::default().fg(color)));
+            used += cells;
+        }
+        line.push(Span::raw(" ".repeat(bar_width - used)));
+        line.push(Span::raw(format!("] {:>5.1}% ", core.busy())));
+    }
+
+    let paragraph = Paragraph::new(lines.into_iter().map(Line::from).collect::<Vec<Line>>());
+    f.render_widget(paragraph, area);
+}
+
+fn draw_network(
+    f: &mut ratatui::Frame,
+    area: ratatui::layout::Rect,
+    interfaces: &[InterfaceStats],
+    exclude_virtual: bool,
+) {
+    let rows: Vec<Row> = interfaces
+        .iter()
+        .map(|iface| {
+            let style = if iface.operstate == "down" {
+                Style::default().fg(Color::DarkGray)
+            } else {
+                Style::default()
+            };
+            Row::new(vec![
+                iface.name.clone(),
+                iface.operstate.clone(),
+                iface
+                    .speed_mbps
+                    .map(|speed| format!("{} Mb/s", speed))
+                    .unwrap_or_else(|| "-".to_string()),
+                format!("{}/s", format_bytes(iface.rx_bytes_per_sec)),
+                format!("{}/s", format_bytes(iface.tx_bytes_per_sec)),
+                format!("{:.1}", iface.rx_packets_per_sec),
+                format!("{:.1}", iface.tx_packets_per_sec),
+                format!("{:.1}/{:.1}", iface.rx_errors_per_sec, iface.tx_errors_per_sec),
+                format!("{:.1}/{:.1}", iface.rx_drops_per_sec, iface.tx_drops_per_sec),
+                format_bytes(iface.rx_bytes_total as f64),
+                format_bytes(iface.tx_bytes_total as f64),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let title = if exclude_virtual {
+        "Network (physical interfaces)"
+    } else {
+        "Network (all interfaces)"
+    };
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(12),  // Interface
+            Constraint::Length(8),   // State
+            Constraint::Length(11),  // Speed
+            Constraint::Length(12),  // RX/s
+            Constraint::Length(12),  // TX/s
+            Constraint::Length(9),   // RX pkt/s
+            Constraint::Length(9),   // TX pkt/s
+            Constraint::Length(11),  // Errors/s
+            Constraint::Length(11),  // Drops/s
+            Constraint::Length(11),  // RX total
+            Constraint::Length(11),  // TX total
+        ],
+    )
+    .header(Row::new(vec![
+        "IFACE", "STATE", "SPEED", "RX", "TX", "RX PKT/s", "TX PKT/s", "ERR/s R/T", "DROP/s R/T", "RX TOTAL", "TX TOTAL",
+    ]))
+    .block(Block::default().title(title).borders(Borders::ALL));
+
+    f.render_widget(table, area);
+}
+
+fn draw_sockets(f: &mut ratatui::Frame, area: Rect, app: &App) {
+    let sockets = &app.snapshot.sockets;
+    let mut title = "Sockets".to_string();
+    if app.listening_only {
+        title.push_str(" (listening)");
+    }
+    if !app.show_unix_sockets {
+        title.push_str(" (no unix)");
+    }
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    let tcp: Vec<String> = tcp_state_counts(sockets)
+        .iter()
+        .map(|(state, count)| format!("{} {}", state, count))
+        .collect();
+    let count = |protocols: &[Protocol]| sockets.iter().filter(|s| protocols.contains(&s.protocol)).count();
+    let summary = format!(
+        "TCP: {}\nUDP: {}  UNIX: {}  Total: {}",
+        if tcp.is_empty() { "none".to_string() } else { tcp.join("  ") },
+        count(&[Protocol::Udp, Protocol::Udp6]),
+        count(&[Protocol::Unix]),
+        sockets.len()
+    );
+    f.render_widget(Paragraph::new(summary), rows[0]);
+
+    let table_rows: Vec<Row> = app
+        .sockets()
+        .skip(app.sockets_scroll)
+        .map(|s| {
+            let processes: Vec<String> = s.processes.iter().map(|(pid, command)| format!("{}/{}", pid, command)).collect();
+            Row::new(vec![
+                s.protocol.name().to_string(),
+                s.state.clone(),
+                s.local.clone(),
+                s.remote.clone(),
+                s.inode.to_string(),
+                processes.join(","),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        table_rows,
+        [
+            Constraint::Length(6),   // Protocol
+            Constraint::Length(13),  // State
+            Constraint::Length(46),  // Local
+            Constraint::Length(46),  // Remote
+            Constraint::Length(10),  // Inode
+            Constraint::Min(20),     // Processes
+        ],
+    )
+    .header(Row::new(vec!["PROTO", "STATE", "LOCAL", "REMOTE", "INODE", "PROCESS"]));
+    f.render_widget(table, rows[1]);
+}
+
+fn draw_memory(f: &mut ratatui::Frame, area: ratatui::layout::Rect, memory: &MemoryStats, scroll: usize) {
+    let block = Block::default().title("Memory").borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let huge_page_mb = memory.huge_page_size_kb as f64 / 1024.0;
+    let gauges = [
+        ("Used", memory.used_mb, memory.total_mb, Color::Green),
+        ("Buff/cache", memory.buffers_mb + memory.cached_mb, memory.total_mb, Color::Blue),
+        ("Available", memory.available_mb, memory.total_mb, Color::Cyan),
+        ("Shared", memory.shared_mb, memory.total_mb, Color::Magenta),
+        ("Slab", memory.slab_mb, memory.total_mb, Color::Yellow),
+        ("Swap used", memory.swap_used_mb, memory.swap_total_mb, Color::Red),
+        (
+            "Huge pages",
+            (memory.huge_pages_total - memory.huge_pages_free.min(memory.huge_pages_total)) as f64 * huge_page_mb,
+            memory.huge_pages_total as f64 * huge_page_mb,
+            Color::LightRed,
+        ),
+    ];
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(gauges.len() as u16 + 1), Constraint::Min(0)].as_ref())
+        .split(inner);
+    let gauge_rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(1); gauges.len()])
+        .split(rows[0]);
+
+    for (i, (label, value, total, color)) in gauges.into_iter().enumerate() {
+        let ratio = if total > 0.0 { (value / total).clamp(0.0, 1.0) } else { 0.0 };
+        let gauge = LineGauge::default()
+            .label(format!("{:<11}{:>10.1} / {:>10.1} MiB {:>5.1}%", label, value, total, ratio * 100.0))
+            .filled_style(Style::default().fg(color))
+            .ratio(ratio);
+        f.render_widget(gauge, gauge_rows[i]);
+    }
+
+    // The rest of /proc/meminfo, with a bar relative to MemTotal
+    let bar_width = 30;
+    let detail: Vec<Row> = memory
+        .entries
+        .iter()
+        .skip(scroll)
+        .map(|entry| {
+            if !entry.is_kb {
+                return Row::new(vec![entry.name.clone(), format!("{} pages", entry.value)]);
+            }
+            let mb = entry.value as f64 / 1024.0;
+            let ratio = if memory.total_mb > 0.0 { (mb / memory.total_mb).min(1.0) } else { 0.0 };
+            let filled = (ratio * bar_width as f64).round() as usize;
+            Row::new(vec![
+                entry.name.clone(),
+                format!("{:.1} MiB", mb),
+                format!("{:.1}%", ratio * 100.0),
+                format!("{}{}", "█".repeat(filled), "░".repeat(bar_width - filled)),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        detail,
+        [
+            Constraint::Length(18),  // Name
+            Constraint::Length(16),  // Value
+            Constraint::Length(8),   // Share of MemTotal
+            Constraint::Length(bar_width as u16),
+        ],
+    )
+    .header(Row::new(vec!["FIELD", "VALUE", "%TOTAL", ""]));
+    f.render_widget(table, rows[1]);
+}
+
+/// Title for the detail views, and the details of the process they show or
+/// why there are none yet.
+fn current_detail(app: &App) -> (String, Result<&ProcessDetail, &'static str>) {
+    let pid = app.options.detail_pid.unwrap_or_default();
+    let command = app
+        .snapshot
+        .processes
+        .iter()
+        .find(|p| p.pid == pid)
+        .map(|p| p.command.as_str());
+    let title = format!("Process {} ({})", pid, command.unwrap_or("exited"));
+    let detail = match &app.snapshot.detail {
+        Some(detail) if detail.pid == pid => Ok(detail),
+        _ if app.replay.is_some() => Err("No details were recorded for this process"),
+        _ if command.is_none() => Err("The process has exited"),
+        _ => Err("Loading..."),
+    };
+    (title, detail)
+}
+
+fn draw_process_detail(f: &mut ratatui::Frame, area: Rect, app: &App) {
+    let (title, detail) = current_detail(app);
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let lines = match detail {
+        Ok(detail) => {
+            let tasks = app.detail_process().map_or(&[][..], |p| &p.tasks);
+            process_detail_lines(detail, app.detail_threads.then_some(tasks))
+        }
+        Err(message) => vec![Line::raw(message)],
+    };
+    let scroll = app.detail_scroll.min(lines.len().saturating_sub(1));
+    let paragraph = Paragraph::new(lines).block(block).scroll((scroll as u16, 0));
+    f.render_widget(paragraph, area);
+}
+
+/// The detail pane's content; also tells the app how far it can scroll.
+/// `threads` is `None` while the thread list is hidden.
+pub fn process_detail_lines(detail: &ProcessDetail, threads: Option<&[ThreadInfo]>) -> Vec<Line<'static>> {
+    let heading = |title: &str| Line::styled(title.to_string(), Style::default().add_modifier(Modifier::BOLD));
+    let field = |name: &str, value: String| Line::raw(format!("  {:<28}{}", name, value));
+    let unavailable = || "(unavailable)".to_string();
+
+    let mut lines = vec![
+        heading("Process"),
+        field("Command line", detail.cmdline.join(" ")),
+        field("Executable", detail.exe.clone().unwrap_or_else(unavailable)),
+        field("Working dir", detail.cwd.clone().unwrap_or_else(unavailable)),
+        field("Root", detail.root.clone().unwrap_or_else(unavailable)),
+        field(
+            "Started",
+            detail
+                .start_time
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(unavailable),
+        ),
+        Line::raw(""),
+    ];
+
+    match threads {
+        Some(threads) => {
+            lines.push(heading("Threads"));
+            lines.push(Line::raw(format!(
+                "  {:>8}  {:<16} {:<2} {:>6} {:>4} {:>12} {:>12}",
+                "TID", "NAME", "ST", "%CPU", "CPU", "VOL CTXT", "NONVOL CTXT"
+            )));
+            lines.extend(threads.iter().map(|t| {
+                Line::raw(format!(
+                    "  {:>8}  {:<16} {:<2} {:>6.1} {:>4} {:>12} {:>12}",
+                    t.tid,
+                    t.name,
+                    t.state,
+                    t.cpu_usage,
+                    t.processor.map(|p| p.to_string()).unwrap_or_default(),
+                    t.voluntary_ctxt_switches,
+                    t.nonvoluntary_ctxt_switches
+                ))
+            }));
+        }
+        None => lines.push(heading("Threads (H: Show)")),
+    }
+    lines.push(Line::raw(""));
+    lines.push(heading("Status"));
+    lines.extend(detail.status.iter().map(|(key, value)| field(key, value.clone())));
+
+    lines.push(Line::raw(""));
+    lines.push(heading("Limits"));
+    lines.push(Line::raw(format!("  {:<26}{:>22}{:>22}  {}", "LIMIT", "SOFT", "HARD", "UNITS")));
+    lines.extend(detail.limits.iter().map(|limit| {
+        Line::raw(format!("  {:<26}{:>22}{:>22}  {}", limit.name, limit.soft, limit.hard, limit.units))
+    }));
+
+    lines.push(Line::raw(""));
+    lines.push(heading("Cgroups"));
+    lines.extend(detail.cgroups.iter().map(|cgroup| Line::raw(format!("  {}", cgroup))));
+
+    lines.push(Line::raw(""));
+    lines.push(heading("Namespaces"));
+    lines.extend(detail.namespaces.iter().map(|(name, target)| field(name, target.clone())));
+
+    lines.push(Line::raw(""));
+    lines.push(heading("Open files"));
+    lines.push(field("Descriptors", format!("{} (o: List)", detail.open_files.len())));
+
+    lines.push(Line::raw(""));
+    lines.push(heading("Environment"));
+    match &detail.environ {
+        Some(environ) => lines.extend(environ.iter().map(|var| Line::raw(format!("  {}", var)))),
+        None => lines.push(Line::raw(format!("  {}", unavailable()))),
+    }
+    lines
+}
+
+fn draw_open_files(f: &mut ratatui::Frame, area: Rect, app: &App) {
+    let (title, detail) = current_detail(app);
+    let files = match detail {
+        Ok(detail) => &detail.open_files,
+        Err(message) => {
+            let block = Block::default().title(title).borders(Borders::ALL);
+            f.render_widget(Paragraph::new(message).block(block), area);
+            return;
+        }
+    };
+    let rows: Vec<Row> = files
+        .iter()
+        .skip(app.files_scroll)
+        .map(|file| {
+            Row::new(vec![
+                file.fd.to_string(),
+                file.kind.name().to_string(),
+                file.flags_description(),
+                file.pos.map(|pos| pos.to_string()).unwrap_or_default(),
+                file.target.clone(),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(6),   // FD
+            Constraint::Length(11),  // Type
+            Constraint::Length(26),  // Flags
+            Constraint::Length(12),  // Offset
+            Constraint::Min(20),     // Target
+        ],
+    )
+    .header(Row::new(vec!["FD", "TYPE", "FLAGS", "POS", "TARGET"]))
+    .block(
+        Block::default()
+            .title(format!("{}: {} open files", title, files.len()))
+            .borders(Borders::ALL),
+    );
+    f.render_widget(table, area);
+}
+
+fn draw_file_search(f: &mut ratatui::Frame, area: Rect, app: &App) {
+    let rows: Vec<Row> = app
+        .file_users
+        .iter()
+        .skip(app.files_scroll)
+        .map(|user| {
+            Row::new(vec![
+                user.pid.to_string(),
+                user.command.clone(),
+                user.file.fd.to_string(),
+                user.file.kind.name().to_string(),
+                user.file.flags_description(),
+                user.file.target.clone(),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),   // PID
+            Constraint::Length(16),  // Command
+            Constraint::Length(6),   // FD
+            Constraint::Length(11),  // Type
+            Constraint::Length(26),  // Flags
+            Constraint::Min(20),     // Target
+        ],
+    )
+    .header(Row::new(vec!["PID", "COMMAND", "FD", "TYPE", "FLAGS", "TARGET"]))
+    .block(
+        Block::default()
+            .title(format!(
+                "Open '{}': {} descriptors (O: Search again)",
+                app.file_query,
+                app.file_users.len()
+            ))
+            .borders(Borders::ALL),
+    );
+    f.render_widget(table, area);
+}
+
+fn draw_disks(f: &mut ratatui::Frame, area: ratatui::layout::Rect, disks: &[DiskStats]) {
+    let disk_row = |disk: &DiskStats, name: String| {
+        Row::new(vec![
+            name,
+            format!("{}/s", format_bytes(disk.read_bytes_per_sec)),
+            format!("{}/s", format_bytes(disk.write_bytes_per_sec)),
+            format!("{:.1}", disk.read_iops),
+            format!("{:.1}", disk.write_iops),
+            format!("{:.2}", disk.await_ms),
+            format!("{:.1}", disk.util_percent),
+            format_bytes(disk.read_bytes_total as f64),
+            format_bytes(disk.write_bytes_total as f64),
+        ])
+    };
+
+    let mut rows = Vec::new();
+    for disk in disks {
+        rows.push(disk_row(disk, disk.name.clone()));
+        for (i, partition) in disk.partitions.iter().enumerate() {
+            let guide = if i + 1 == disk.partitions.len() { "└─" } else { "├─" };
+            rows.push(
+                disk_row(partition, format!("{} {}", guide, partition.name))
+                    .style(Style::default().fg(Color::DarkGray)),
+            );
+        }
+    }
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(14),  // Device
+            Constraint::Length(12),  // Read/s
+            Constraint::Length(12),  // Write/s
+            Constraint::Length(9),   // Read IOPS
+            Constraint::Length(9),   // Write IOPS
+            Constraint::Length(10),  // Await
+            Constraint::Length(7),   // Util
+            Constraint::Length(11),  // Read total
+            Constraint::Length(11),  // Write total
+        ],
+    )
+    .header(Row::new(vec![
+        "DEVICE", "READ", "WRITE", "R IOPS", "W IOPS", "AWAIT ms", "%UTIL", "READ TOTAL", "WRITE TOTAL",
+    ]))
+    .block(Block::default().title("Disk I/O").borders(Borders::ALL));
+
+    f.render_widget(table, area);
+}
+
+fn draw_crash_tracking(f: &mut ratatui::Frame, area: ratatui::layout::Rect, crash_history: &[String]) {
+    let block = Block::default().title("Crash Tracking").borders(Borders::ALL);
+    let content = crash_history.join("\n");
+    let paragraph = Paragraph::new(content).block(block).style(
+        Style::default()
+    );
+    f.render_widget(paragraph, area);
+}
+
+/// The whole process table as a forest, with the app's columns and the
+/// tree guides in front of the command.
+fn draw_process_tree(f: &mut ratatui::Frame, area: Rect, app: &App) {
+    let tree = app.tree_rows();
+    let position = app.tree_position(&tree);
+    // Keep the highlighted node on screen even if a refresh moved it
+    let scroll = scroll_to(position, app.tree_scroll);
+
+    let rows: Vec<Row> = tree
+        .iter()
+        .enumerate()
+        .skip(scroll)
+        .take(PAGE_ROWS)
+        .map(|(i, row)| {
+            let p = &app.snapshot.processes[row.index];
+            let style = row_style(i == position, app.tagged.contains(&p.pid));
+            Row::new(app.columns.iter().map(|c| {
+                let value = c.value(p);
+                if !c.is_text() {
+                    return value;
+                }
+                let marker = if row.collapsed && row.has_children { "[+] " } else { "" };
+                format!("{}{}{}", row.prefix, marker, value)
+                    .chars()
+                    .skip(app.text_scroll)
+                    .collect()
+            }))
+            .style(style)
+        })
+        .collect();
+
+    let widths = app.columns.iter().map(|c| {
+        if c.is_text() {
+            Constraint::Min(c.width())
+        } else {
+            Constraint::Length(c.width())
+        }
+    });
+    let table = Table::new(rows, widths)
+        .header(Row::new(app.columns.iter().map(|c| c.title())))
+        .block(
+            Block::default()
+                .title(format!("Process Tree ({} processes)", app.snapshot.processes.len()))
+                .borders(Borders::ALL),
+        );
+    f.render_widget(table, area);
+}
+
+fn help_text(app: &App) -> String {
+    let sort_label = match app.sort_criteria {
+        SortCriteria::CPU => "Sorting by: CPU",
+        SortCriteria::Memory => "Sorting by: Memory",
+        SortCriteria::PID => "Sorting by: PID",
+        SortCriteria::PR => "Sorting by: Priority",
+        SortCriteria::Virtual => "Sorting by: Virtual",
+        SortCriteria::Shared => "Sorting by: Shared",
+        SortCriteria::Swap => "Sorting by: Swap",
+        SortCriteria::MemPercent => "Sorting by: %MEM",
+        SortCriteria::Pss => "Sorting by: PSS",
+        SortCriteria::Uss => "Sorting by: USS",
+    };
+    let view_label = match app.view_state {
+        ViewState::Processes => "View: Processes",
+        ViewState::CrashTracking => "View: Crash Tracking",
+        ViewState::ProcessTree => "View: Process Tree (Tab/+/-: Expand/collapse  *: Expand all  space: Tag  Enter: Details  k/K/s/w: Signal)",
+        ViewState::Network => "View: Network (v: Toggle loopback/virtual)",
+        ViewState::Sockets => "View: Sockets (l: Listening only  x: Toggle unix  ↑/↓: Scroll)",
+        ViewState::Disks => "View: Disk I/O",
+        ViewState::Memory => "View: Memory (M: Back)",
+        ViewState::Detail => "View: Process Detail (Esc: Back  o: Open files  H: Threads  ↑/↓/PgUp/PgDn: Scroll)",
+        ViewState::Files => "View: Open Files (Esc: Back  ↑/↓/PgUp/PgDn: Scroll)",
+        ViewState::FileSearch => "View: Who Has It Open (Esc: Back  ↑/↓/PgUp/PgDn: Scroll)",
+    };
+    let cpu_mode_label = match app.options.cpu_mode {
+        CpuMode::Irix => "CPU mode: Irix (per core)",
+        CpuMode::Solaris => "CPU mode: Solaris (all cores)",
+    };
+    let mut help_text = format!(
+        "{}  {}\n{}\nKeys: q: Quit  O: Who has file open  t: Show tree  I: CPU mode  M: Memory panel  ←/→: Switch View  ↑/↓: Navigate    Sort by: c: CPU  m: Memory  p: PID  r: Priority\nList: Enter: Details  o: Open files  H: Threads  f: Fields  C: Command line  </>: Scroll  /: Search  n/N: Next/prev match  \\: Filter",
+        sort_label, cpu_mode_label, view_label
+    );
+    if let Some(filter) = &app.filter {
+        help_text.push_str(&format!("  [filter: {}]", filter.as_str()));
+    }
+    help_text.push_str(
+        "\nProcess: k: Signal  K: Kill subtree  s/w: Suspend/wake  [/]: Nice +/-  S: Policy  i: I/O prio  a: Affinity  Tag: space  T: Subtree  F: Filtered  U: None",
+    );
+    if !app.tagged.is_empty() {
+        help_text.push_str(&format!("  [{} tagged]", app.tagged.len()));
+    }
+    if let Some(replay) = &app.replay {
+        help_text.push_str(&format!(
+            "\nReplay: {}/{} at {}{}  P: Pause  ,/.: Step  g: Go to time",
+            replay.position + 1,
+            replay.len,
+            app.snapshot.time.format("%Y-%m-%d %H:%M:%S"),
+            if replay.paused { " [paused]" } else { "" }
+        ));
+    }
+    if let Some(prompt) = &app.prompt {
+        let label = match prompt.kind {
+            PromptKind::Seek => "Go to time (HH:MM[:SS]): ",
+            PromptKind::Search => "Search: ",
+            PromptKind::Filter => "Filter (e.g. user==root && cpu>5): ",
+            PromptKind::OpenFile => "Who has open (path or socket:[inode]): ",
+        };
+        help_text.push_str(&format!("\n{}{}_", label, prompt.input));
+        if let (PromptKind::Filter, Some(error)) = (prompt.kind, &app.filter_error) {
+            help_text.push_str(&format!("  ({})", error));
+        }
+    } else if let Some(status) = &app.status {
+        help_text.push_str(&format!("\n{}", status));
+    }
+    help_text
+}
+
+fn draw_help_section(f: &mut ratatui::Frame, area: ratatui::layout::Rect, help_text: String) {
+    let block = Block::default().title("Help").borders(Borders::ALL);
+    let paragraph = Paragraph::new(help_text).block(block).style(
+        Style::default()
+    );
+    f.render_widget(paragraph, area);
+}
+
+fn draw_process_list(f: &mut ratatui::Frame, area: ratatui::layout::Rect, app: &App) {
+    let columns = &app.columns;
+    let rows: Vec<Row> = app
+        .rows()
+        .skip(app.scroll_offset)
+        .take(PAGE_ROWS)
+        .enumerate()
+        .map(|(i, (p, thread))| {
+            let selected = app.scroll_offset + i == app.selected_index;
+            // Tags belong to processes, so thread rows are never shown tagged
+            let style = match thread {
+                Some(_) if !selected => Style::default().fg(Color::Cyan),
+                _ => row_style(selected, thread.is_none() && app.tagged.contains(&p.pid)),
+            };
+            Row::new(columns.iter().map(|c| {
+                let value = match thread {
+                    Some(t) => {
+                        let last = p.tasks.iter().rev().find(|t| t.tid != p.pid).map(|t| t.tid) == Some(t.tid);
+                        c.thread_value(p, t, last)
+                    }
+                    None => c.value(p),
+                };
+                if c.is_text() {
+                    value.chars().skip(app.text_scroll).collect()
+                } else {
+                    value
+                }
+            }))
+            .style(style)
+        })
+        .collect();
+
+    // The command soaks up whatever width is left
+    let widths = columns.iter().map(|c| {
+        if c.is_text() {
+            Constraint::Min(c.width())
+        } else {
+            Constraint::Length(c.width())
+        }
+    });
+    let table = Table::new(rows, widths)
+        .header(Row::new(columns.iter().map(|c| c.title())))
+        .block(Block::default().title("Processes").borders(Borders::ALL));
+
+    f.render_widget(table, area);
+}
+
+/// Selected rows are yellow, tagged ones magenta; a tagged row under the
+/// cursor is both yellow and underlined.
+fn row_style(selected: bool, tagged: bool) -> Style {
+    match (selected, tagged) {
+        (true, true) => Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+        (true, false) => Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        (false, true) => Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD),
+        (false, false) => Style::default(),
+    }
+}
+
+/// Popup listing every column, with the visible ones ticked.
+fn draw_field_picker(f: &mut ratatui::Frame, area: Rect, app: &App, cursor: usize) {
+    let width = 56.min(area.width);
+    let height = (ALL_COLUMNS.len() as u16 + 3).min(area.height);
+    let popup = Rect::new(area.x + (area.width - width) / 2, area.y, width, height);
+
+    let mut lines: Vec<Line> = ALL_COLUMNS
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let shown = if app.columns.contains(c) { "[x]" } else { "[ ]" };
+            let sorted = if c.sort() == Some(app.sort_criteria) { "*" } else { " " };
+            let text = format!("{} {}{:<8} {}", shown, sorted, c.title(), c.description());
+            if i == cursor {
+                Line::styled(text, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            } else {
+                Line::raw(text)
+            }
+        })
+        .collect();
+    lines.push(Line::styled(
+        "space: Show/hide  s: Sort by  Esc: Close",
+        Style::default().fg(Color::DarkGray),
+    ));
+
+    let block = Block::default().title("Fields").borders(Borders::ALL);
+    f.render_widget(Clear, popup);
+    f.render_widget(Paragraph::new(lines).block(block), popup);
+}
+
+/// A popup list with the `cursor` row highlighted, scrolled to keep it in
+/// view, and a line of `keys` under it.
+fn draw_menu(f: &mut ratatui::Frame, area: Rect, title: String, width: u16, items: Vec<String>, cursor: usize, keys: &str) {
+    let width = width.min(area.width);
+    let height = (items.len() as u16 + 3).min(area.height);
+    let popup = Rect::new(area.x + (area.width - width) / 2, area.y, width, height);
+
+    // Keep the cursor in the middle of the window once the list overflows
+    let rows = height.saturating_sub(3) as usize;
+    let first = cursor.saturating_sub(rows / 2).min(items.len().saturating_sub(rows));
+    let mut lines: Vec<Line> = items
+        .into_iter()
+        .enumerate()
+        .skip(first)
+        .take(rows)
+        .map(|(i, text)| {
+            if i == cursor {
+                Line::styled(text, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            } else {
+                Line::raw(text)
+            }
+        })
+        .collect();
+    lines.push(Line::styled(keys.to_string(), Style::default().fg(Color::DarkGray)));
+
+    let block = Block::default().title(title).borders(Borders::ALL);
+    f.render_widget(Clear, popup);
+    f.render_widget(Paragraph::new(lines).block(block), popup);
+}
+
+fn warning_style() -> Style {
+    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+}
+
+/// Most processes a confirmation lists before summing up the rest.
+const CONFIRM_LIST_ROWS: usize = 10;
+
+/// `123 (sleep)` for one process, `3 tagged processes` for more.
+fn targets_label(targets: &[SignalTarget]) -> String {
+    match targets {
+        [target] => format!("{} ({})", target.pid, target.command),
+        _ => format!("{} tagged processes", targets.len()),
+    }
+}
+
+/// A yes/no popup asking `question` about `targets`, with any `notes`
+/// under the process details.
+fn draw_confirm(f: &mut ratatui::Frame, area: Rect, question: Line, targets: &[SignalTarget], notes: Vec<Line>) {
+    let mut lines = vec![question, Line::raw("")];
+    match targets {
+        [target] => {
+            lines.push(Line::raw(format!("  PID:     {}", target.pid)));
+            lines.push(Line::raw(format!("  Command: {}", target.command)));
+            lines.push(Line::raw(format!("  User:    {}", target.user)));
+        }
+        _ => {
+            lines.extend(targets.iter().take(CONFIRM_LIST_ROWS).map(|target| {
+                Line::raw(format!("  {:<8}{:<17}{}", target.pid, target.command, target.user))
+            }));
+            if targets.len() > CONFIRM_LIST_ROWS {
+                lines.push(Line::raw(format!("  ... and {} more", targets.len() - CONFIRM_LIST_ROWS)));
+            }
+        }
+    }
+
+    lines.extend(notes);
+    lines.push(Line::styled("y: Yes  n: Cancel", Style::default().fg(Color::DarkGray)));
+
+    let width = 50.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+
+    let block = Block::default().title("Confirm").borders(Borders::ALL);
+    f.render_widget(Clear, popup);
+    f.render_widget(Paragraph::new(lines).block(block), popup);
+}
+
+fn draw_shutdown(f: &mut ratatui::Frame, area: Rect, app: &App, shutdown: &Shutdown) {
+    let width = 60.min(area.width);
+    let height = (shutdown.alive.len() as u16 + 5).min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+
+    let remaining = shutdown.deadline.saturating_duration_since(std::time::Instant::now());
+    let progress = if shutdown.escalated {
+        "SIGKILL sent, waiting for exit".to_string()
+    } else {
+        format!("SIGTERM sent, SIGKILL in {:.1}s", remaining.as_secs_f64())
+    };
+    let mut lines = vec![
+        Line::styled(progress, warning_style()),
+        Line::raw(format!("Still running: {} of {}", shutdown.alive.len(), shutdown.pids.len())),
+    ];
+    lines.extend(shutdown.alive.iter().map(|pid| {
+        let command = app
+            .snapshot
+            .processes
+            .iter()
+            .find(|p| p.pid == *pid)
+            .map_or("", |p| p.command.as_str());
+        Line::raw(format!("  {:<8}{}", pid, command))
+    }));
+    let keys = if shutdown.escalated { "" } else { "k: Kill now  Esc: Stop waiting" };
+    lines.push(Line::styled(keys, Style::default().fg(Color::DarkGray)));
+
+    let title = format!("Shutting down {} ({})", shutdown.target.pid, shutdown.target.command);
+    let block = Block::default().title(title).borders(Borders::ALL);
+    f.render_widget(Clear, popup);
+    f.render_widget(Paragraph::new(lines).block(block), popup);
+}